@@ -2,6 +2,7 @@ use arena_space::body::examples::{earth_body, luna};
 use arena_space::body::Planet;
 use arena_space::colony::economy::ProductionUnit;
 use arena_space::colony::*;
+use arena_space::command::Command;
 use arena_space::components::*;
 use arena_space::ships::drives::Drive;
 use arena_space::ships::freighter_assignment::Assignment;
@@ -23,24 +24,23 @@ fn main() {
     let rng = &mut WyRng::seed_from_u64(0);
 
     for _ in 0..20 {
-        let f = state.state.freighter.create(
+        let freighter = state.create_freighter(
             get_random_freighter(rng),
             FreighterLinks {
                 location: farm_colony,
             },
         );
 
-        {
-            let f = Valid::assert(f);
-            let assignment = &mut state.state.freighter.assignment;
-            assignment.insert(f, Some(Assignment::Route(city_colony, farm_colony)));
-        }
+        state.apply(Command::AssignFreighter {
+            freighter,
+            assignment: Some(Assignment::Route(city_colony, farm_colony)),
+        });
     }
 
-    state.update_by(2.0 * YR);
+    state.update_by(2.0 * YR).unwrap();
 
     for _ in 0..6 {
-        state.update_by(30.0 * DAY);
+        state.update_by(30.0 * DAY).unwrap();
 
         state.state.time.print();
         state.state.colony.print();
@@ -82,7 +82,7 @@ fn get_test_state() -> TestState {
     let earth_pop = Population::in_millions(100.0);
     let luna_pop = Population::in_millions(10.0);
 
-    let farm_colony = state.state.colony.create(
+    let farm_colony = state.create_colony(
         Colony {
             name: "Farmland Earth".to_string(),
             population: earth_pop,
@@ -95,14 +95,17 @@ fn get_test_state() -> TestState {
     let production_unit = ProductionUnit::new(
         food_required * 1.075,
         Resource::Food,
-        state.state.body.properties.get(earth),
+        state.state.body.get_habitability(earth, &state.state.star),
     );
-    let food_production = state.state.colony.production.get_mut(Facility::Farmland);
-    food_production.insert(farm_colony, production_unit);
+    state.apply(Command::InsertProduction {
+        colony: farm_colony,
+        facility: Facility::Farmland,
+        unit: production_unit,
+    });
 
-    state.state.colony.production_cycle();
+    state.update_by(1.0 * DAY).unwrap();
 
-    let city_colony = state.state.colony.create(
+    let city_colony = state.create_colony(
         Colony {
             name: "Lunar City".to_string(),
             population: luna_pop,
@@ -111,8 +114,11 @@ fn get_test_state() -> TestState {
     );
 
     // create price gradient
-    let food_prices = state.state.colony.resources.price.get_mut(Resource::Food);
-    food_prices.insert(city_colony, Resource::Food.get_default_price() * 4.0);
+    state.apply(Command::SetPrice {
+        colony: city_colony,
+        resource: Resource::Food,
+        price: Resource::Food.get_default_price() * 4.0,
+    });
 
     TestState {
         state,
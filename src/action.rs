@@ -1,10 +1,12 @@
 use crate::colony::ColonyLinks;
 use crate::*;
+use serde::{Deserialize, Serialize};
 
 pub enum ColonyActions {
     Body(ColonyBodyAction),
 }
 
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum ColonyBodyAction {
     StartColony,
 }
@@ -0,0 +1,37 @@
+use arena_space::scenario::Scenario;
+use std::process::exit;
+
+/// Runs a scenario file to its end date, exiting with 1 if any assertion fails and 2 on other errors
+fn main() {
+    let path = match std::env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: scenario <file.ron>");
+            exit(2);
+        }
+    };
+
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        exit(2);
+    });
+
+    let scenario = Scenario::from_ron(&text).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        exit(2);
+    });
+
+    let stdout = std::io::stdout();
+    let failures = scenario.run(&mut stdout.lock()).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        exit(2);
+    });
+
+    if !failures.is_empty() {
+        for failure in &failures {
+            eprintln!("{}", failure);
+        }
+
+        exit(1);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::components::Fraction;
+use crate::components::{Flux, Fraction, Temperature};
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result};
 use Habitability::*;
 use Pressure::*;
@@ -44,7 +45,7 @@ impl Default for Habitability {
 }
 
 /// Describes the conditions of a body
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct BodyProperties {
     pub surface: Surface,
     pub pressure: Pressure,
@@ -81,18 +82,64 @@ impl Display for BodyProperties {
 }
 
 impl BodyProperties {
-    pub fn get_habitability(&self) -> Habitability {
+    pub fn get_habitability(&self, temperature: Temperature) -> Habitability {
         self.surface
             .get_habitability()
             .min(self.pressure.get_habitability())
             .min(self.hydrosphere.get_habitability())
             .min(self.biosphere.get_habitability())
             .min(self.oxygen.get_habitability())
+            .min(get_temperature_habitability(temperature))
+    }
+
+    /// The fraction of incoming light that is reflected back into space
+    pub fn get_albedo(&self) -> f64 {
+        match (self.surface, self.pressure) {
+            (Gaseous, _) => 0.5,
+            // reflected by thick clouds before reaching the surface, e.g., Venus
+            (_, Crushing) => 0.75,
+            (Frozen, _) => 0.6,
+            (Continental { .. }, _) => 0.3,
+            (Oceanic, _) => 0.25,
+            (Volcanic, _) => 0.2,
+            (Barren, _) => 0.15,
+        }
+    }
+
+    /// The warming of the surface above its equilibrium temperature by the atmosphere
+    pub fn get_greenhouse_warming(&self) -> Temperature {
+        if self.surface == Gaseous {
+            return Temperature::zero();
+        }
+
+        match self.pressure {
+            Vacuum => Temperature::zero(),
+            Thin => Temperature::in_k(5.0),
+            Ideal => Temperature::in_k(33.0),
+            High => Temperature::in_k(60.0),
+            Crushing => Temperature::in_k(500.0),
+        }
+    }
+
+    /// The mean surface temperature of a body receiving the given stellar flux
+    pub fn get_surface_temperature(&self, flux: Flux) -> Temperature {
+        Temperature::of_equilibrium(flux, self.get_albedo()) + self.get_greenhouse_warming()
+    }
+}
+
+fn get_temperature_habitability(temperature: Temperature) -> Habitability {
+    match temperature.value {
+        kelvin if kelvin < 240.0 => Hostile,
+        kelvin if kelvin < 273.0 => Marginal,
+        kelvin if kelvin < 313.0 => Optimal,
+        kelvin if kelvin < 343.0 => Marginal,
+        kelvin if kelvin < 400.0 => Hostile,
+        _ => Uninhabitable,
     }
 }
 
 /// Describes the surface of a body (if it has one)
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Surface {
     /// The surface of a gas giant
     Gaseous,
@@ -122,7 +169,7 @@ impl Surface {
 }
 
 /// Describes the atmospheric pressure of a body
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Pressure {
     /// No atmosphere
     Vacuum,
@@ -153,7 +200,7 @@ impl Pressure {
 /// Created by a molten core in rocky planets and metallic hydrogen in gas giants.
 ///
 /// Used during stellar system generation to determine whether the planet can support an atmosphere.
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Magnetosphere {
     /// No strong magnetic field protects this body from stellar wind. Lighter elements will be ionized and blown away.
     Absent,
@@ -162,7 +209,7 @@ pub enum Magnetosphere {
 }
 
 /// The availability of water
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Hydrosphere {
     /// No water present
     None,
@@ -183,7 +230,7 @@ impl Hydrosphere {
 }
 
 /// The presence and evolutionary stage of life on this body
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Biosphere {
     /// Lifeless
     None,
@@ -203,7 +250,7 @@ impl Biosphere {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum AtmosphericOxygen {
     None,
     Partial,
@@ -237,7 +284,8 @@ pub mod examples {
 
     #[test]
     fn moon_habitability() {
-        assert_eq!(Hostile, moon().get_habitability());
+        let temperature = moon().get_surface_temperature(Flux::SOLAR_CONSTANT);
+        assert_eq!(Hostile, moon().get_habitability(temperature));
     }
 
     pub fn earth() -> BodyProperties {
@@ -255,7 +303,16 @@ pub mod examples {
 
     #[test]
     fn earth_habitability() {
-        assert_eq!(Optimal, earth().get_habitability());
+        let temperature = earth().get_surface_temperature(Flux::SOLAR_CONSTANT);
+        assert!((temperature.value - 288.0).abs() < 2.0);
+        assert_eq!(Optimal, earth().get_habitability(temperature));
+    }
+
+    #[test]
+    fn earth_is_hostile_where_mars_orbits() {
+        let flux = Flux::SOLAR_CONSTANT * (1.0 / 1.524f64.powi(2));
+        let temperature = earth().get_surface_temperature(flux);
+        assert_eq!(Hostile, earth().get_habitability(temperature));
     }
 
     pub fn jupiter() -> BodyProperties {
@@ -271,7 +328,9 @@ pub mod examples {
 
     #[test]
     fn jupiter_habitability() {
-        assert_eq!(Uninhabitable, jupiter().get_habitability());
+        let flux = Flux::SOLAR_CONSTANT * (1.0 / 5.2f64.powi(2));
+        let temperature = jupiter().get_surface_temperature(flux);
+        assert_eq!(Uninhabitable, jupiter().get_habitability(temperature));
     }
 
     pub fn venus() -> BodyProperties {
@@ -287,7 +346,10 @@ pub mod examples {
 
     #[test]
     fn venus_habitability() {
-        assert_eq!(Uninhabitable, venus().get_habitability());
+        let flux = Flux::SOLAR_CONSTANT * (1.0 / 0.723f64.powi(2));
+        let temperature = venus().get_surface_temperature(flux);
+        assert!((temperature.value - 737.0).abs() < 30.0);
+        assert_eq!(Uninhabitable, venus().get_habitability(temperature));
     }
 
     #[test]
@@ -301,6 +363,9 @@ pub mod examples {
             magnetosphere: Magnetosphere::Present,
         };
 
-        assert_eq!(Habitability::Marginal, bp.get_habitability());
+        assert_eq!(
+            Habitability::Marginal,
+            bp.get_habitability(Temperature::in_k(300.0))
+        );
     }
 }
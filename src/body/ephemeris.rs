@@ -0,0 +1,120 @@
+//! Orbital phases of real bodies, so that a star system can start where its bodies are in the sky
+
+use crate::body::Body;
+use crate::components::*;
+use crate::star::StarSystem;
+use crate::time::{DateTime, TimeState};
+use serde::Deserialize;
+
+/// The mean longitude of each listed body at an epoch
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ephemeris {
+    pub epoch: DateTime,
+    pub bodies: Vec<EphemerisEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EphemerisEntry {
+    pub name: String,
+    /// Degrees, measured from the same direction as the longitude of the ascending node
+    pub mean_longitude: f64,
+}
+
+impl Ephemeris {
+    pub fn from_ron(text: &str) -> Result<Self, ron::Error> {
+        ron::from_str(text)
+    }
+
+    /// The planets, dwarf planets, Luna, the Galilean moons and Titan at J2000
+    pub fn bundled() -> Self {
+        Self::from_ron(include_str!("../../data/ephemeris.ron"))
+            .expect("the bundled ephemeris is valid")
+    }
+
+    /// Sets the phase of each listed body so that it is in place at the start date, when game
+    /// time is zero. Bodies that are not listed keep their phase.
+    pub fn apply(&self, star_system: &mut StarSystem, start: DateTime) {
+        let elapsed = TimeState::new(self.epoch).get_time_at(start);
+
+        for planet in &mut star_system.planets {
+            self.apply_to_body(&mut planet.body, elapsed);
+
+            for moon in &mut planet.moons {
+                self.apply_to_body(moon, elapsed);
+            }
+        }
+    }
+
+    fn apply_to_body(&self, body: &mut Body, elapsed: TimeFloat) {
+        if let Some(entry) = self.bodies.iter().find(|entry| entry.name == body.name) {
+            body.orbit.offset = entry.get_offset(&body.orbit, elapsed);
+        }
+    }
+}
+
+impl EphemerisEntry {
+    /// The offset that gives the body its mean anomaly after the time elapsed since the epoch
+    fn get_offset(&self, orbit: &Orbit, elapsed: TimeFloat) -> Angle {
+        let longitude_of_periapsis = orbit.ascending_node + orbit.periapsis;
+        let mean_anomaly = Angle::in_deg(self.mean_longitude) - longitude_of_periapsis
+            + elapsed.value * orbit.angular_speed;
+
+        -mean_anomaly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::star::examples::sol_system;
+    use crate::state::State;
+    use crate::time::get_date;
+
+    /// A distance of one meter in the direction of the target
+    fn get_direction(from: Position, to: Position) -> Distance {
+        let distance = to - from;
+        distance / distance.magnitude().value
+    }
+
+    #[test]
+    fn bundled_ephemeris_parses() {
+        assert_eq!(17, Ephemeris::bundled().bodies.len());
+    }
+
+    #[test]
+    fn bundled_ephemeris_lists_every_planet_and_dwarf_planet() {
+        let ephemeris = Ephemeris::bundled();
+
+        for planet in sol_system().planets {
+            let name = planet.body.name;
+            assert!(
+                ephemeris.bodies.iter().any(|entry| entry.name == name),
+                "{} is missing",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn jupiter_and_saturn_are_in_conjunction_at_the_end_of_2020() {
+        let mut sol = sol_system();
+        Ephemeris::bundled().apply(&mut sol, get_date(2020, 12, 21));
+
+        let mut state = State::default();
+        state.create_star_system(sol);
+
+        let get_position = |name| {
+            let body = state.body.get_by_name(name).unwrap();
+            state.body.get_position(body, TimeFloat::in_s(0.0))
+        };
+
+        let earth = get_position("Earth");
+        let jupiter = get_direction(earth, get_position("Jupiter"));
+        let saturn = get_direction(earth, get_position("Saturn"));
+
+        // seen from Earth, the two were a tenth of a degree apart,
+        // and the chord between nearby directions is about the angle in radians
+        let chord = (jupiter - saturn).magnitude();
+        assert!(chord.value < Angle::in_deg(1.0).value);
+    }
+}
@@ -2,11 +2,13 @@ use crate::star::{Star, Stars};
 use crate::*;
 
 pub use components::*;
+use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
 
 mod components;
+pub mod ephemeris;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Body {
     pub name: String,
     pub mass: Mass,
@@ -36,19 +38,24 @@ impl BodyLinks {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Bodies {
     pub alloc: Allocator<Body>,
 
     pub name: Component<Body, String>,
     pub mass: Component<Body, Mass>,
     pub radius: Component<Body, Length>,
-    pub orbit: Component<Body, BodyOrbit>,
+    pub orbit: Component<Body, Orbit>,
+    /// The body orbited, or `None` for bodies orbiting the star
+    pub parent: Component<Body, Option<Id<Body>>>,
     pub properties: Component<Body, BodyProperties>,
 
     pub population: HashMap<Id<Body>, Population>,
 
     pub star: Component<Body, Id<Star>>,
+
+    #[serde(skip)]
+    names: HashMap<String, Id<Body>>,
 }
 
 impl Bodies {
@@ -60,14 +67,23 @@ impl Bodies {
     ) -> Id<Body> {
         let id = self.alloc.create();
 
+        self.names.entry(row.name.clone()).or_insert(id);
         self.name.insert(id, row.name);
 
         self.mass.insert(id, row.mass);
 
         self.radius.insert(id, row.radius);
 
-        let orbit = self.get_orbit(links.parent, row.orbit);
-        self.orbit.insert(id, orbit);
+        if let Some(parent) = links.parent {
+            assert_eq!(
+                links.star,
+                *self.star.get(parent),
+                "Parent body must orbit the same star."
+            );
+        }
+
+        self.orbit.insert(id, row.orbit);
+        self.parent.insert(id, links.parent);
 
         self.properties.insert(id, row.properties);
 
@@ -77,47 +93,78 @@ impl Bodies {
         id
     }
 
-    fn get_orbit(&self, parent: Option<Id<Body>>, params: Orbit) -> BodyOrbit {
-        let parent = parent.map(|parent| self.orbit.get(parent)).map(|orbit| {
-            assert!(
-                orbit.parent.is_none(),
-                "Cannot use a moon as a parent body."
-            );
-            orbit.params
-        });
+    /// The position relative to the star, found by adding the positions of each parent in turn
+    pub fn get_position<I: ValidId<Body>>(&self, id: I, time: TimeFloat) -> Position {
+        let mut position = self.orbit.get(id).calculate_position(time);
+        let mut parent = *self.parent.get(id);
+
+        while let Some(body) = parent {
+            position = position + self.orbit.get(body).calculate_position(time);
+            parent = *self.parent.get(body);
+        }
 
-        BodyOrbit { params, parent }
+        position.into()
     }
 
-    pub fn get_position<I: ValidId<Body>>(&self, id: I, time: TimeFloat) -> Position {
-        self.orbit.get(id).calculate_position(time)
+    pub fn get_galaxy_position<I: ValidId<Body>>(
+        &self,
+        id: I,
+        time: TimeFloat,
+        stars: &Stars,
+    ) -> GalaxyPosition {
+        let star = *stars.position.get(self.star.get(id));
+        GalaxyPosition::from_local(star, self.get_position(id, time))
     }
 
+    pub fn get_parent<I: ValidId<Body>>(&self, id: I) -> Option<Id<Body>> {
+        *self.parent.get(id)
+    }
+
+    /// Bodies orbiting the same star are compared directly to avoid rounding at galactic scale
     pub fn get_distance(
         &self,
         from: Id<Body>,
         to: Id<Body>,
         time: TimeFloat,
-        star: &Stars,
-    ) -> Distance {
-        let system_from = self.star.get(from);
-        let system_to = self.star.get(to);
-
-        if system_from == system_to {
+        stars: &Stars,
+    ) -> GalaxyDistance {
+        if self.star.get(from) == self.star.get(to) {
             let from = self.get_position(from, time);
             let to = self.get_position(to, time);
 
-            to - from
+            GalaxyDistance::from_local(to - from)
         } else {
-            let from = star.position.get(system_from);
-            let to = star.position.get(system_to);
+            let from = self.get_galaxy_position(from, time, stars);
+            let to = self.get_galaxy_position(to, time, stars);
 
             to - from
         }
     }
 
-    pub fn get_habitability(&self, id: Id<Body>) -> Habitability {
-        self.properties.get(id).get_habitability()
+    /// The orbit radius of the planet, or of the planet that the moon orbits
+    pub fn get_distance_from_star<I: ValidId<Body>>(&self, id: I) -> Length {
+        let mut body = id.id();
+
+        while let Some(parent) = *self.parent.get(body) {
+            body = parent;
+        }
+
+        self.orbit.get(body).radius
+    }
+
+    pub fn get_flux<I: ValidId<Body>>(&self, id: I, stars: &Stars) -> Flux {
+        let star_type = stars.star_type.get(self.star.get(id));
+        star_type.get_flux(self.get_distance_from_star(id))
+    }
+
+    pub fn get_surface_temperature<I: ValidId<Body>>(&self, id: I, stars: &Stars) -> Temperature {
+        let flux = self.get_flux(id, stars);
+        self.properties.get(id).get_surface_temperature(flux)
+    }
+
+    pub fn get_habitability(&self, id: Id<Body>, stars: &Stars) -> Habitability {
+        let temperature = self.get_surface_temperature(id, stars);
+        self.properties.get(id).get_habitability(temperature)
     }
 
     pub fn get_land_area<I: ValidId<Body>>(&self, id: I) -> Area {
@@ -134,13 +181,18 @@ impl Bodies {
         }
     }
 
+    /// The first body created with the name
     pub fn get_by_name(&self, name: &str) -> Option<Id<Body>> {
-        self.name
-            .iter()
-            .zip(self.alloc.ids())
-            .into_iter()
-            .filter_map(|(n, id)| if *n == name { Some(id) } else { None })
-            .next()
+        self.names.get(name).copied()
+    }
+
+    /// Rebuilds the lookup by name, which is not saved
+    pub fn rebuild_index(&mut self) {
+        self.names.clear();
+
+        for id in self.alloc.ids() {
+            self.names.entry(self.name.get(id).clone()).or_insert(id);
+        }
     }
 
     pub fn get_gravity(&self, id: Id<Body>) -> Acceleration {
@@ -162,6 +214,10 @@ impl Bodies {
                 radius,
                 angular_speed,
                 offset: Default::default(),
+                eccentricity: 0.0,
+                periapsis: Default::default(),
+                inclination: Default::default(),
+                ascending_node: Default::default(),
             })
         } else {
             None
@@ -179,11 +235,15 @@ impl Bodies {
             radius,
             angular_speed,
             offset: Default::default(),
+            eccentricity: 0.0,
+            periapsis: Default::default(),
+            inclination: Default::default(),
+            ascending_node: Default::default(),
         })
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Planet {
     pub body: Body,
     pub moons: Vec<Body>,
@@ -210,18 +270,26 @@ impl Bodies {
 
 pub mod population {
     use super::*;
-    use crate::colony::Colonies;
+    use crate::colony::Colony;
     use std::collections::hash_map::Entry;
 
     impl Bodies {
-        pub fn sum_population(&mut self, colonies: &Colonies) {
+        pub fn sum_population(
+            &mut self,
+            population: &Component<Colony, Population>,
+            colony_body: &Component<Colony, Id<Body>>,
+        ) {
             self.population.clear();
-            self.add_colony_population(colonies);
+            self.add_colony_population(population, colony_body);
         }
 
-        fn add_colony_population(&mut self, colonies: &Colonies) {
-            let population = colonies.people.population.iter();
-            let body = colonies.body.iter();
+        fn add_colony_population(
+            &mut self,
+            population: &Component<Colony, Population>,
+            colony_body: &Component<Colony, Id<Body>>,
+        ) {
+            let population = population.iter();
+            let body = colony_body.iter();
 
             for (pop, body) in population.zip(body) {
                 self.add_population(body, pop);
@@ -254,6 +322,10 @@ pub mod examples {
                     radius: Length::in_m(57.909e9),
                     angular_speed: Angle::TWO_PI / Duration::in_days(87.969),
                     offset: 0.25 * Angle::TWO_PI,
+                    eccentricity: 0.2056,
+                    periapsis: Angle::in_deg(29.124),
+                    inclination: Angle::in_deg(7.005),
+                    ascending_node: Angle::in_deg(48.331),
                 },
                 properties: BodyProperties {
                     surface: Surface::Barren,
@@ -278,6 +350,10 @@ pub mod examples {
                     radius: Length::in_m(108.209e9),
                     angular_speed: Angle::TWO_PI / Duration::in_days(224.7),
                     offset: 0.6 * Angle::TWO_PI,
+                    eccentricity: 0.0068,
+                    periapsis: Angle::in_deg(54.884),
+                    inclination: Angle::in_deg(3.395),
+                    ascending_node: Angle::in_deg(76.680),
                 },
                 properties: BodyProperties {
                     surface: Surface::Barren,
@@ -304,11 +380,16 @@ pub mod examples {
             name: "Earth".to_string(),
             mass: Mass::in_kg(5.972e24),
             radius: Length::in_m(6371e3),
-            orbit: Orbit::from_period(
-                Length::in_m(149.60e9),
-                Duration::in_days(365.25),
-                Default::default(),
-            ),
+            orbit: Orbit {
+                eccentricity: 0.0167,
+                // the longitude of perihelion, as Earth's orbit defines the plane
+                periapsis: Angle::in_deg(102.937),
+                ..Orbit::from_period(
+                    Length::in_m(149.60e9),
+                    Duration::in_days(365.25),
+                    Default::default(),
+                )
+            },
             properties: BodyProperties {
                 surface: Surface::Continental {
                     land: Fraction::clamp(0.204),
@@ -327,11 +408,15 @@ pub mod examples {
             name: "Luna".to_string(),
             mass: Mass::in_kg(7.34767309e22),
             radius: Length::in_m(1737.1e3),
-            orbit: Orbit::from_period(
-                Length::in_m(384_400e3),
-                Duration::in_days(27.322),
-                Default::default(),
-            ),
+            orbit: Orbit {
+                eccentricity: 0.0549,
+                inclination: Angle::in_deg(5.145),
+                ..Orbit::from_period(
+                    Length::in_m(384_400e3),
+                    Duration::in_days(27.322),
+                    Default::default(),
+                )
+            },
 
             properties: BodyProperties {
                 surface: Surface::Barren,
@@ -354,6 +439,10 @@ pub mod examples {
                     radius: Length::in_m(227.923e9),
                     angular_speed: Angle::TWO_PI / Duration::in_days(686.98),
                     offset: 0.8 * Angle::TWO_PI,
+                    eccentricity: 0.0934,
+                    periapsis: Angle::in_deg(286.502),
+                    inclination: Angle::in_deg(1.850),
+                    ascending_node: Angle::in_deg(49.558),
                 },
                 properties: BodyProperties {
                     surface: Surface::Barren,
@@ -364,7 +453,496 @@ pub mod examples {
                     magnetosphere: Magnetosphere::Absent,
                 },
             },
+            moons: vec![
+                moon(
+                    "Phobos",
+                    1.0659e16,
+                    11.08,
+                    moon_orbit(9_376.0, 0.318_910, 0.0151),
+                    airless(),
+                ),
+                moon(
+                    "Deimos",
+                    1.4762e15,
+                    6.2,
+                    moon_orbit(23_463.2, 1.263_0, 0.000_33),
+                    airless(),
+                ),
+            ],
+        }
+    }
+
+    pub fn jupiter() -> Planet {
+        Planet {
+            body: Body {
+                name: "Jupiter".to_string(),
+                mass: Mass::in_kg(1898.19e24),
+                radius: Length::in_m(69_911e3),
+                orbit: Orbit {
+                    radius: 5.202_887 * AU,
+                    angular_speed: Angle::TWO_PI / Duration::in_days(4332.59),
+                    offset: Default::default(),
+                    eccentricity: 0.0484,
+                    periapsis: Angle::in_deg(274.255),
+                    inclination: Angle::in_deg(1.304),
+                    ascending_node: Angle::in_deg(100.474),
+                },
+                properties: gas_giant(),
+            },
+            moons: vec![
+                moon(
+                    "Io",
+                    8.931_938e22,
+                    1821.6,
+                    moon_orbit(421_700.0, 1.769_138, 0.0041),
+                    volcanic(),
+                ),
+                moon(
+                    "Europa",
+                    4.799_844e22,
+                    1560.8,
+                    moon_orbit(671_034.0, 3.551_181, 0.009),
+                    icy(),
+                ),
+                moon(
+                    "Ganymede",
+                    1.4819e23,
+                    2634.1,
+                    moon_orbit(1_070_412.0, 7.154_553, 0.0013), // the only moon known to generate its own magnetic field
+                    BodyProperties {
+                        magnetosphere: Magnetosphere::Present,
+                        ..icy()
+                    },
+                ),
+                moon(
+                    "Callisto",
+                    1.075_938e23,
+                    2410.3,
+                    moon_orbit(1_882_709.0, 16.689_018, 0.0074),
+                    icy(),
+                ),
+            ],
+        }
+    }
+
+    pub fn saturn() -> Planet {
+        Planet {
+            body: Body {
+                name: "Saturn".to_string(),
+                mass: Mass::in_kg(568.34e24),
+                radius: Length::in_m(58_232e3),
+                orbit: Orbit {
+                    radius: 9.536_676 * AU,
+                    angular_speed: Angle::TWO_PI / Duration::in_days(10_759.22),
+                    offset: Default::default(),
+                    eccentricity: 0.0539,
+                    periapsis: Angle::in_deg(338.936),
+                    inclination: Angle::in_deg(2.486),
+                    ascending_node: Angle::in_deg(113.662),
+                },
+                properties: gas_giant(),
+            },
+            moons: vec![
+                moon(
+                    "Mimas",
+                    3.7493e19,
+                    198.2,
+                    moon_orbit(185_539.0, 0.942_422, 0.0196),
+                    icy(),
+                ),
+                moon(
+                    "Enceladus",
+                    1.080_22e20,
+                    252.1,
+                    moon_orbit(237_948.0, 1.370_218, 0.0047),
+                    icy(),
+                ),
+                moon(
+                    "Tethys",
+                    6.174_49e20,
+                    531.1,
+                    moon_orbit(294_619.0, 1.887_802, 0.0001),
+                    icy(),
+                ),
+                moon(
+                    "Dione",
+                    1.095_452e21,
+                    561.4,
+                    moon_orbit(377_396.0, 2.736_915, 0.0022),
+                    icy(),
+                ),
+                moon(
+                    "Rhea",
+                    2.306_518e21,
+                    763.8,
+                    moon_orbit(527_108.0, 4.518_212, 0.0013),
+                    icy(),
+                ),
+                moon(
+                    "Titan",
+                    1.3452e23,
+                    2574.73,
+                    moon_orbit(1_221_870.0, 15.945, 0.0288), // a nitrogen atmosphere half again as dense as Earth's, over lakes of methane
+                    BodyProperties {
+                        pressure: Pressure::High,
+                        ..icy()
+                    },
+                ),
+                moon(
+                    "Iapetus",
+                    1.805_635e21,
+                    734.5,
+                    Orbit {
+                        inclination: Angle::in_deg(15.47),
+                        ..moon_orbit(3_560_820.0, 79.3215, 0.0286)
+                    },
+                    icy(),
+                ),
+            ],
+        }
+    }
+
+    pub fn uranus() -> Planet {
+        Planet {
+            body: Body {
+                name: "Uranus".to_string(),
+                mass: Mass::in_kg(86.813e24),
+                radius: Length::in_m(25_362e3),
+                orbit: Orbit {
+                    radius: 19.189_165 * AU,
+                    angular_speed: Angle::TWO_PI / Duration::in_days(30_688.5),
+                    offset: Default::default(),
+                    eccentricity: 0.0473,
+                    periapsis: Angle::in_deg(96.937),
+                    inclination: Angle::in_deg(0.773),
+                    ascending_node: Angle::in_deg(74.017),
+                },
+                properties: gas_giant(),
+            },
+            // Uranus is tipped on its side, and its moons orbit above its equator
+            moons: vec![
+                uranian_moon(
+                    "Miranda",
+                    6.293e19,
+                    235.8,
+                    moon_orbit(129_390.0, 1.413_479, 0.0013),
+                ),
+                uranian_moon(
+                    "Ariel",
+                    1.2331e21,
+                    578.9,
+                    moon_orbit(191_020.0, 2.520_379, 0.0012),
+                ),
+                uranian_moon(
+                    "Umbriel",
+                    1.2885e21,
+                    584.7,
+                    moon_orbit(266_000.0, 4.144_177, 0.0039),
+                ),
+                uranian_moon(
+                    "Titania",
+                    3.4550e21,
+                    788.4,
+                    moon_orbit(435_910.0, 8.705_872, 0.0011),
+                ),
+                uranian_moon(
+                    "Oberon",
+                    3.1104e21,
+                    761.4,
+                    moon_orbit(583_520.0, 13.463_239, 0.0014),
+                ),
+            ],
+        }
+    }
+
+    fn uranian_moon(name: &str, mass: f64, radius: f64, orbit: Orbit) -> Body {
+        let orbit = Orbit {
+            inclination: Angle::in_deg(97.77),
+            ..orbit
+        };
+
+        moon(name, mass, radius, orbit, icy())
+    }
+
+    pub fn neptune() -> Planet {
+        Planet {
+            body: Body {
+                name: "Neptune".to_string(),
+                mass: Mass::in_kg(102.413e24),
+                radius: Length::in_m(24_622e3),
+                orbit: Orbit {
+                    radius: 30.069_923 * AU,
+                    angular_speed: Angle::TWO_PI / Duration::in_days(60_182.0),
+                    offset: Default::default(),
+                    eccentricity: 0.0086,
+                    periapsis: Angle::in_deg(273.181),
+                    inclination: Angle::in_deg(1.770),
+                    ascending_node: Angle::in_deg(131.784),
+                },
+                properties: gas_giant(),
+            },
+            moons: vec![
+                moon(
+                    "Proteus",
+                    4.4e19,
+                    210.0,
+                    moon_orbit(117_647.0, 1.122_315, 0.0005),
+                    icy(),
+                ),
+                moon(
+                    "Triton",
+                    2.1389e22,
+                    1353.4,
+                    Orbit {
+                        // retrograde, as Triton was likely captured from the Kuiper belt
+                        inclination: Angle::in_deg(156.885),
+                        ..moon_orbit(354_759.0, 5.876_854, 0.000_016)
+                    },
+                    icy(),
+                ),
+                moon(
+                    "Nereid",
+                    3.1e19,
+                    170.0,
+                    Orbit {
+                        inclination: Angle::in_deg(7.23),
+                        ..moon_orbit(5_513_818.0, 360.13, 0.7507)
+                    },
+                    icy(),
+                ),
+            ],
+        }
+    }
+
+    pub fn ceres() -> Planet {
+        Planet {
+            body: Body {
+                name: "Ceres".to_string(),
+                mass: Mass::in_kg(9.3835e20),
+                radius: Length::in_m(469.73e3),
+                orbit: Orbit {
+                    radius: 2.7675 * AU,
+                    angular_speed: Angle::TWO_PI / Duration::in_days(1680.99),
+                    offset: Default::default(),
+                    eccentricity: 0.0785,
+                    periapsis: Angle::in_deg(73.60),
+                    inclination: Angle::in_deg(10.59),
+                    ascending_node: Angle::in_deg(80.27),
+                },
+                properties: BodyProperties {
+                    hydrosphere: Hydrosphere::Frozen,
+                    ..airless()
+                },
+            },
             moons: vec![],
         }
     }
+
+    pub fn pluto() -> Planet {
+        Planet {
+            body: Body {
+                name: "Pluto".to_string(),
+                mass: Mass::in_kg(1.303e22),
+                radius: Length::in_m(1188.3e3),
+                orbit: Orbit {
+                    radius: 39.482_117 * AU,
+                    angular_speed: Angle::TWO_PI / Duration::in_days(90_560.0),
+                    offset: Default::default(),
+                    eccentricity: 0.2488,
+                    periapsis: Angle::in_deg(113.765),
+                    inclination: Angle::in_deg(17.140),
+                    ascending_node: Angle::in_deg(110.304),
+                },
+                properties: icy(),
+            },
+            moons: vec![moon(
+                "Charon",
+                1.586e21,
+                606.0,
+                Orbit {
+                    // above Pluto's equator, which is tipped past its side
+                    inclination: Angle::in_deg(119.6),
+                    ..moon_orbit(19_591.0, 6.387_230, 0.0002)
+                },
+                icy(),
+            )],
+        }
+    }
+
+    pub fn eris() -> Planet {
+        Planet {
+            body: Body {
+                name: "Eris".to_string(),
+                mass: Mass::in_kg(1.6466e22),
+                radius: Length::in_m(1163e3),
+                orbit: Orbit {
+                    radius: 67.864 * AU,
+                    angular_speed: Angle::TWO_PI / Duration::in_days(203_830.0),
+                    offset: Default::default(),
+                    eccentricity: 0.4361,
+                    periapsis: Angle::in_deg(151.639),
+                    inclination: Angle::in_deg(44.040),
+                    ascending_node: Angle::in_deg(35.951),
+                },
+                properties: icy(),
+            },
+            moons: vec![],
+        }
+    }
+
+    /// Given its mass in kg and its radius in km
+    fn moon(name: &str, mass: f64, radius: f64, orbit: Orbit, properties: BodyProperties) -> Body {
+        Body {
+            name: name.to_string(),
+            mass: Mass::in_kg(mass),
+            radius: radius * KM,
+            orbit,
+            properties,
+        }
+    }
+
+    /// In the plane of the star system, given its semi-major axis in km and its period in days
+    fn moon_orbit(radius: f64, period: f64, eccentricity: f64) -> Orbit {
+        Orbit {
+            eccentricity,
+            ..Orbit::from_period(radius * KM, Duration::in_days(period), Default::default())
+        }
+    }
+
+    fn airless() -> BodyProperties {
+        BodyProperties {
+            surface: Surface::Barren,
+            pressure: Pressure::Vacuum,
+            oxygen: AtmosphericOxygen::None,
+            hydrosphere: Hydrosphere::None,
+            biosphere: Biosphere::None,
+            magnetosphere: Magnetosphere::Absent,
+        }
+    }
+
+    fn icy() -> BodyProperties {
+        BodyProperties {
+            surface: Surface::Frozen,
+            hydrosphere: Hydrosphere::Frozen,
+            ..airless()
+        }
+    }
+
+    fn volcanic() -> BodyProperties {
+        BodyProperties {
+            surface: Surface::Volcanic,
+            ..airless()
+        }
+    }
+
+    fn gas_giant() -> BodyProperties {
+        BodyProperties {
+            surface: Surface::Gaseous,
+            pressure: Pressure::Crushing,
+            magnetosphere: Magnetosphere::Present,
+            ..airless()
+        }
+    }
+
+    /// Halley's Comet, whose orbit is far from circular
+    pub fn halley() -> Planet {
+        Planet {
+            body: Body {
+                name: "Halley".to_string(),
+                mass: Mass::in_kg(2.2e14),
+                radius: Length::in_m(5.5e3),
+                orbit: Orbit {
+                    radius: 17.834 * AU,
+                    angular_speed: Angle::TWO_PI / (75.32 * YR),
+                    offset: Default::default(),
+                    eccentricity: 0.96714,
+                    periapsis: Angle::in_deg(111.33),
+                    inclination: Angle::in_deg(162.26),
+                    ascending_node: Angle::in_deg(58.42),
+                },
+                properties: BodyProperties {
+                    surface: Surface::Frozen,
+                    pressure: Pressure::Vacuum,
+                    oxygen: AtmosphericOxygen::None,
+                    hydrosphere: Hydrosphere::None,
+                    biosphere: Biosphere::None,
+                    magnetosphere: Magnetosphere::Absent,
+                },
+            },
+            moons: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::examples::*;
+    use super::*;
+    use crate::star::examples::sol;
+    use crate::state::State;
+
+    fn get_circular_body(name: &str, radius: Length) -> Body {
+        Body {
+            name: name.to_string(),
+            orbit: Orbit::from_period(radius, Duration::in_days(10.0), Angle::default()),
+            ..luna()
+        }
+    }
+
+    #[test]
+    fn positions_are_resolved_through_every_parent() {
+        let mut state = State::default();
+        let star = state.star.create(sol());
+        let bodies = &mut Vec::new();
+
+        let planet = get_circular_body("Planet", Length::in_m(1000.0));
+        let moon = get_circular_body("Moon", Length::in_m(100.0));
+        let station = get_circular_body("Station", Length::in_m(10.0));
+
+        let planet = state.body.create(planet, BodyLinks::planet(star), bodies);
+        let moon = state
+            .body
+            .create(moon, BodyLinks::moon(star, planet), bodies);
+        let station = state
+            .body
+            .create(station, BodyLinks::moon(star, moon), bodies);
+
+        let time = TimeFloat::in_s(0.0);
+        assert_eq!(
+            Position::in_m(0.0, 1110.0),
+            state.body.get_position(station, time)
+        );
+
+        // changes to a parent's orbit carry through to its satellites
+        state.body.orbit.get_mut(planet).radius = Length::in_m(2000.0);
+
+        assert_eq!(
+            Position::in_m(0.0, 2110.0),
+            state.body.get_position(station, time)
+        );
+        assert_eq!(Some(moon), state.body.get_parent(station));
+    }
+
+    #[test]
+    fn moons_receive_the_flux_of_their_planet() {
+        let mut state = State::default();
+        state.create_star_system(crate::star::examples::sol_system());
+
+        let earth = state.body.get_by_name("Earth").unwrap();
+        let luna = state.body.get_by_name("Luna").unwrap();
+        let venus = state.body.get_by_name("Venus").unwrap();
+
+        assert_eq!(
+            state.body.get_flux(earth, &state.star),
+            state.body.get_flux(luna, &state.star)
+        );
+        assert_eq!(
+            Habitability::Optimal,
+            state.body.get_habitability(earth, &state.star)
+        );
+        assert_eq!(
+            Habitability::Uninhabitable,
+            state.body.get_habitability(venus, &state.star)
+        );
+    }
 }
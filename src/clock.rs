@@ -0,0 +1,225 @@
+use crate::components::*;
+use crate::system_state::SystemState;
+use crate::time::{StdDuration, TimeError};
+
+/// How quickly game time passes relative to real time
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SimSpeed {
+    Paused,
+    /// Seconds of game time per second of real time
+    Multiplier(f64),
+}
+
+impl SimSpeed {
+    pub const REAL_TIME: SimSpeed = SimSpeed::Multiplier(1.0);
+    pub const MINUTE_PER_SECOND: SimSpeed = SimSpeed::Multiplier(60.0);
+    pub const HOUR_PER_SECOND: SimSpeed = SimSpeed::Multiplier(Duration::SECONDS_PER_HOUR);
+    pub const DAY_PER_SECOND: SimSpeed = SimSpeed::Multiplier(Duration::SECONDS_PER_DAY);
+
+    pub fn get_game_time(self, real_time: StdDuration) -> Duration {
+        match self {
+            SimSpeed::Paused => Duration::zero(),
+            SimSpeed::Multiplier(multiplier) => Duration::from(real_time) * multiplier,
+        }
+    }
+}
+
+impl Default for SimSpeed {
+    fn default() -> Self {
+        SimSpeed::REAL_TIME
+    }
+}
+
+/// The result of advancing the clock by one frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frame {
+    /// Game time simulated during the frame
+    pub advanced: Duration,
+    /// Game time that is owed after reaching the step limit, to be simulated in later frames
+    pub behind: Duration,
+    /// Owed game time beyond the catch-up limit, which is skipped rather than simulated
+    pub dropped: Duration,
+}
+
+impl Frame {
+    pub fn is_behind(&self) -> bool {
+        self.behind > Duration::zero()
+    }
+}
+
+/// Drives a `SystemState` from real time, e.g., once per rendered frame
+#[derive(Debug, Clone)]
+pub struct SimClock {
+    pub state: SystemState,
+    speed: SimSpeed,
+    /// The most game time simulated in a single frame, limiting the work done when catching up
+    max_step: Duration,
+    /// The most game time owed between frames, so that a clock whose frames are too slow to
+    /// catch up does not fall further behind with every frame
+    max_behind: Duration,
+    behind: Duration,
+}
+
+impl SimClock {
+    pub const DEFAULT_MAX_STEP: Duration = Duration::in_days(7.0);
+    pub const DEFAULT_MAX_BEHIND: Duration = Duration::in_days(28.0);
+
+    pub fn new(state: SystemState) -> Self {
+        Self {
+            state,
+            speed: SimSpeed::default(),
+            max_step: Self::DEFAULT_MAX_STEP,
+            max_behind: Self::DEFAULT_MAX_BEHIND,
+            behind: Duration::zero(),
+        }
+    }
+
+    pub fn get_speed(&self) -> SimSpeed {
+        self.speed
+    }
+
+    /// Changing the speed keeps any owed game time, except when pausing, which drops it
+    pub fn set_speed(&mut self, speed: SimSpeed) {
+        if speed == SimSpeed::Paused {
+            self.behind = Duration::zero();
+        }
+
+        self.speed = speed;
+    }
+
+    pub fn get_max_step(&self) -> Duration {
+        self.max_step
+    }
+
+    pub fn set_max_step(&mut self, max_step: Duration) {
+        debug_assert!(max_step > Duration::zero());
+        self.max_step = max_step;
+    }
+
+    pub fn get_max_behind(&self) -> Duration {
+        self.max_behind
+    }
+
+    /// Lowering the limit drops any owed game time above it on the next frame
+    pub fn set_max_behind(&mut self, max_behind: Duration) {
+        debug_assert!(max_behind >= Duration::zero());
+        self.max_behind = max_behind;
+    }
+
+    /// Advances the simulation by the real time elapsed since the previous frame
+    pub fn tick(&mut self, real_time: StdDuration) -> Result<Frame, TimeError> {
+        let target = self.behind + self.speed.get_game_time(real_time);
+
+        let advanced = if target > self.max_step {
+            self.max_step
+        } else {
+            target
+        };
+
+        let behind = target - advanced;
+
+        let dropped = if behind > self.max_behind {
+            behind - self.max_behind
+        } else {
+            Duration::zero()
+        };
+
+        self.behind = behind - dropped;
+
+        if advanced > Duration::zero() {
+            self.state.update_by(advanced)?;
+        }
+
+        Ok(Frame {
+            advanced,
+            behind: self.behind,
+            dropped,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::ChronoDuration;
+
+    fn frame() -> StdDuration {
+        StdDuration::from_millis(500)
+    }
+
+    #[test]
+    fn paused_clock_does_not_advance() {
+        let mut clock = SimClock::new(SystemState::default());
+        clock.set_speed(SimSpeed::Paused);
+        let start = clock.state.state.time.get_date_time();
+
+        let frame = clock.tick(frame()).unwrap();
+
+        assert_eq!(Duration::zero(), frame.advanced);
+        assert_eq!(start, clock.state.state.time.get_date_time());
+    }
+
+    #[test]
+    fn speed_multiplies_real_time() {
+        let mut clock = SimClock::new(SystemState::default());
+        clock.set_speed(SimSpeed::DAY_PER_SECOND);
+        let start = clock.state.state.time.get_date_time();
+
+        let frame = clock.tick(frame()).unwrap();
+
+        assert_eq!(0.5 * DAY, frame.advanced);
+        assert!(!frame.is_behind());
+        assert_eq!(
+            start + ChronoDuration::hours(12),
+            clock.state.state.time.get_date_time()
+        );
+    }
+
+    #[test]
+    fn catch_up_is_limited_per_frame() {
+        let mut clock = SimClock::new(SystemState::default());
+        clock.set_speed(SimSpeed::DAY_PER_SECOND);
+        clock.set_max_step(0.25 * DAY);
+
+        let frame = clock.tick(frame()).unwrap();
+        assert_eq!(0.25 * DAY, frame.advanced);
+        assert_eq!(0.25 * DAY, frame.behind);
+
+        let frame = clock.tick(StdDuration::from_millis(0)).unwrap();
+        assert_eq!(0.25 * DAY, frame.advanced);
+        assert!(!frame.is_behind());
+    }
+
+    #[test]
+    fn owed_time_is_limited() {
+        let mut clock = SimClock::new(SystemState::default());
+        clock.set_speed(SimSpeed::DAY_PER_SECOND);
+        clock.set_max_step(0.25 * DAY);
+        clock.set_max_behind(0.5 * DAY);
+
+        // each slow frame owes more than a step, but the debt stops growing at the limit
+        for _ in 0..3 {
+            let frame = clock.tick(StdDuration::from_secs(2)).unwrap();
+            assert_eq!(0.25 * DAY, frame.advanced);
+            assert_eq!(0.5 * DAY, frame.behind);
+        }
+
+        let frame = clock.tick(StdDuration::from_secs(2)).unwrap();
+        assert_eq!(2.0 * DAY - 0.25 * DAY, frame.dropped);
+    }
+
+    #[test]
+    fn pausing_drops_owed_time() {
+        let mut clock = SimClock::new(SystemState::default());
+        clock.set_speed(SimSpeed::DAY_PER_SECOND);
+        clock.set_max_step(0.25 * DAY);
+        clock.tick(frame()).unwrap();
+
+        clock.set_speed(SimSpeed::Paused);
+        clock.set_speed(SimSpeed::DAY_PER_SECOND);
+
+        let frame = clock.tick(StdDuration::from_millis(100)).unwrap();
+        assert_eq!(0.1 * DAY, frame.advanced);
+        assert!(!frame.is_behind());
+    }
+}
@@ -1,24 +1,22 @@
-use crate::body::BodyProperties;
+use crate::body::Habitability;
 use crate::colony::{Colonies, Colony};
 use crate::components::*;
-use crate::systems::System;
 use gen_id::*;
 use iter_context::{ContextualIterator, Iter, IterMut};
+use serde::{Deserialize, Serialize};
 
 // TODO split economy into production, pricing, decay?
 
-const INTERVAL: Duration = crate::systems::System::ColonyProductionCycle.get_interval();
-
 impl Colonies {
-    pub fn production_cycle(&mut self) {
+    pub fn production_cycle(&mut self, interval: Duration) {
         self.resources.reset_supply_and_demand();
 
         self.request_resources();
-        self.resources.calculate_fulfillment();
+        self.resources.calculate_fulfillment(interval);
         self.read_fulfillment();
-        self.take_inputs();
+        self.take_inputs(interval);
 
-        self.production.output(&mut self.resources);
+        self.production.output(&mut self.resources, interval);
 
         self.resources.add_shipping_flow_to_supply_and_demand();
         self.resources.set_prices();
@@ -35,9 +33,9 @@ impl Colonies {
         self.production.get_fulfillment(&self.resources);
     }
 
-    fn take_inputs(&mut self) {
-        self.production.take_inputs(&mut self.resources);
-        self.people.take_food(&mut self.resources);
+    fn take_inputs(&mut self, interval: Duration) {
+        self.production.take_inputs(&mut self.resources, interval);
+        self.people.take_food(&mut self.resources, interval);
 
         self.resources.set_negatives_to_zero();
     }
@@ -45,7 +43,7 @@ impl Colonies {
 
 /// demand = requested (+ shipping out)
 /// supply = production (+ shipping in)
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Resources {
     pub stockpile: ResourceComponent<Colony, Mass>,
     pub fulfillment: ResourceComponent<Colony, f64>,
@@ -128,7 +126,7 @@ impl Resources {
         }
     }
 
-    fn calculate_fulfillment(&mut self) {
+    fn calculate_fulfillment(&mut self, interval: Duration) {
         self.fulfillment
             .iter_mut()
             .zip(self.stockpile.iter())
@@ -138,7 +136,7 @@ impl Resources {
                     .zip(s.iter())
                     .zip(r.iter())
                     .for_each(|((f, s), r)| {
-                        *f = Self::calculate_fulfillment_inner(*s, *r, INTERVAL);
+                        *f = Self::calculate_fulfillment_inner(*s, *r, interval);
                     });
             });
     }
@@ -201,26 +199,38 @@ impl Resources {
         println!();
     }
 
-    pub fn decay(&mut self) {
-        let year_fraction = System::ResourceDecay.get_interval_as_year_fraction();
+    pub fn decay(&mut self, interval: Duration) {
+        decay_stockpile(&mut self.stockpile, interval);
+    }
 
-        for (component, resource) in self.stockpile.iter_enum_mut() {
-            if let Some(annual_decay) = resource.get_annual_decay() {
-                let decay = annual_decay.powf(year_fraction);
+    pub fn update_shipping_avg(&mut self, interval: Duration) {
+        update_shipping_avg(&mut self.shipping, &mut self.avg_shipping, interval);
+    }
+}
 
-                component.for_each(|value| *value *= decay);
-            }
+/// Split from `Resources` so that it can run alongside other systems, see `parallel::run`
+pub fn decay_stockpile(stockpile: &mut ResourceComponent<Colony, Mass>, interval: Duration) {
+    let year_fraction = interval.as_year_fraction();
+
+    for (component, resource) in stockpile.iter_enum_mut() {
+        if let Some(annual_decay) = resource.get_annual_decay() {
+            let decay = annual_decay.powf(year_fraction);
+
+            component.for_each(|value| *value *= decay);
         }
     }
+}
 
-    pub fn update_shipping_avg(&mut self) {
-        const INTERVAL: Duration = System::ShippingAverage.get_interval();
-
-        for (shipped, average) in self.shipping.iter_mut().zip(self.avg_shipping.iter_mut()) {
-            for (shipped, average) in shipped.iter_mut().zip(average.iter_mut()) {
-                average.add_next(*shipped / INTERVAL);
-                *shipped = Mass::zero();
-            }
+/// Split from `Resources` so that it can run alongside other systems, see `parallel::run`
+pub fn update_shipping_avg(
+    shipping: &mut ResourceComponent<Colony, Mass>,
+    avg_shipping: &mut ResourceComponent<Colony, ExpMovingAvg<MassRate, 30.0>>,
+    interval: Duration,
+) {
+    for (shipped, average) in shipping.iter_mut().zip(avg_shipping.iter_mut()) {
+        for (shipped, average) in shipped.iter_mut().zip(average.iter_mut()) {
+            average.add_next(*shipped / interval);
+            *shipped = Mass::zero();
         }
     }
 }
@@ -260,7 +270,7 @@ fn price_cost_ratio(price: Price, cost: Price) -> f64 {
     price / cost
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Production {
     data: FacilityMap<Colony, ProductionUnit>,
 }
@@ -331,21 +341,21 @@ impl Production {
         }
     }
 
-    fn take_inputs(&mut self, resources: &mut Resources) {
+    fn take_inputs(&mut self, resources: &mut Resources, interval: Duration) {
         for (production, facility) in self.data.iter_enum_mut() {
             for input in facility.get_inputs() {
                 let stockpile = resources.stockpile.get_mut(input.resource);
 
                 for (colony, unit) in production.iter() {
                     let stockpile = stockpile.get_mut(colony);
-                    *stockpile -= unit.get_output() * input.multiplier * INTERVAL;
+                    *stockpile -= unit.get_output() * input.multiplier * interval;
                 }
             }
         }
     }
 
-    fn output(&mut self, resources: &mut Resources) {
-        const RATIO_SCALAR: f64 = 4.0 * INTERVAL / Duration::in_days(365.25);
+    fn output(&mut self, resources: &mut Resources, interval: Duration) {
+        let ratio_scalar = 4.0 * interval.as_year_fraction();
 
         for (production, facility) in self.data.iter_enum_mut() {
             let output = facility.get_output();
@@ -358,7 +368,7 @@ impl Production {
                 let output = unit.get_output();
 
                 let stockpile = stockpile.get_mut(colony);
-                *stockpile += output * INTERVAL;
+                *stockpile += output * interval;
 
                 let supply = supply.get_mut(colony);
                 *supply += output;
@@ -370,9 +380,9 @@ impl Production {
                 let pcr = price_cost_ratio(price[colony], unit.production_cost);
                 let ratio = (dsr * pcr).sqrt();
 
-                let production_multiplier = (ratio - 1.0) * RATIO_SCALAR + 1.0;
+                let production_multiplier = (ratio - 1.0) * ratio_scalar + 1.0;
                 // let production_multiplier =
-                //     (price[colony] / unit.production_cost - 1.0) * RATIO_SCALAR + 1.0;
+                //     (price[colony] / unit.production_cost - 1.0) * ratio_scalar + 1.0;
 
                 unit.capacity *= production_multiplier;
             }
@@ -380,7 +390,7 @@ impl Production {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ProductionUnit {
     pub capacity: MassRate,
     pub fulfillment: f64,
@@ -388,7 +398,7 @@ pub struct ProductionUnit {
 }
 
 impl ProductionUnit {
-    pub fn new(capacity: MassRate, resource: Resource, location: &BodyProperties) -> Self {
+    pub fn new(capacity: MassRate, resource: Resource, location: Habitability) -> Self {
         Self {
             capacity,
             fulfillment: 0.0,
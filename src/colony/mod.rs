@@ -1,13 +1,13 @@
 use crate::body::{Bodies, Body};
 use crate::colony::economy::{Production, Resources};
 use crate::colony::population::People;
-use crate::systems::System;
 use crate::*;
+use serde::{Deserialize, Serialize};
 
 pub mod economy;
 mod population;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Colony {
     pub name: String,
     pub population: Population,
@@ -26,7 +26,7 @@ impl From<Id<Body>> for ColonyLinks {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Colonies {
     pub alloc: Allocator<Colony>,
 
@@ -1,9 +1,10 @@
 use super::*;
+use serde::{Deserialize, Serialize};
 use Resource::Food;
 
 type Satiation = ExpMovingAvg<f64, 15.0>;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct People {
     pub population: Component<Colony, Population>,
     pub satiation: Component<Colony, Satiation>,
@@ -26,7 +27,7 @@ impl People {
             });
     }
 
-    pub fn take_food(&mut self, resources: &mut Resources) {
+    pub fn take_food(&mut self, resources: &mut Resources, interval: Duration) {
         let food_required = self.population.iter().map(|pop| pop.get_food_requirement());
         let satiation = self.satiation.iter_mut();
         let fulfillment = resources.fulfillment.get(Food).iter();
@@ -35,31 +36,43 @@ impl People {
         let iter = food_required.zip(satiation).zip(fulfillment).zip(stockpile);
 
         for (((food_required, satiation), fulfillment), food_stockpile) in iter {
-            *food_stockpile -= food_required * fulfillment * INTERVAL;
+            *food_stockpile -= food_required * fulfillment * interval;
             satiation.add_next(*fulfillment);
         }
     }
 }
 
-const INTERVAL: Duration = crate::systems::System::ColonyProductionCycle.get_interval();
-
 impl Colonies {
     /// Sums the population on each body so that multiple colonies on the same body
     /// will have the effect of crowding each other out
     // TODO area should be a colony component, remove body population
-    pub fn update_population(&mut self, bodies: &mut Bodies) {
-        bodies.sum_population(self);
+    pub fn update_population(&mut self, bodies: &mut Bodies, interval: Duration) {
+        self.people.update_population(&self.body, bodies, interval);
+    }
+}
+
+impl People {
+    /// Split from `Colonies` so that it can run alongside other systems, see `parallel::run`
+    pub fn update_population(
+        &mut self,
+        colony_body: &Component<Colony, Id<Body>>,
+        bodies: &mut Bodies,
+        interval: Duration,
+    ) {
+        bodies.sum_population(&self.population, colony_body);
+
+        let year_fraction = interval.as_year_fraction();
 
-        let population = self.people.population.iter_mut();
-        let satiation = self.people.satiation.iter();
-        let body = self.body.iter();
+        let population = self.population.iter_mut();
+        let satiation = self.satiation.iter();
+        let body = colony_body.iter();
 
         for ((pop, satiation), body) in population.zip(satiation).zip(body) {
             let body_pop = bodies.population.get(body).copied().unwrap_or(*pop);
 
             let land_area = bodies.get_land_area(body);
 
-            *pop *= get_population_multiplier(*satiation, land_area, body_pop);
+            *pop *= get_population_multiplier(*satiation, land_area, body_pop, year_fraction);
         }
     }
 }
@@ -80,9 +93,8 @@ fn get_population_multiplier(
     satiation: Satiation,
     land_area: Area,
     body_population: Population,
+    year_fraction: f64,
 ) -> f64 {
-    const YEAR_FRACTION: f64 = System::ColonyPopulation.get_interval_as_year_fraction();
-
     let max_pop = land_area * MAX_POPULATION_DENSITY;
     let k = max_pop * (BASE_GROWTH_MULTIPLIER / BASE_GROWTH_RATE);
 
@@ -91,7 +103,7 @@ fn get_population_multiplier(
 
     let annual_growth_rate = BASE_GROWTH_MULTIPLIER * k_factor * satiation.value();
 
-    annual_growth_rate.powf(YEAR_FRACTION)
+    annual_growth_rate.powf(year_fraction)
 }
 
 const BASE_GROWTH_RATE: f64 = 0.025;
@@ -105,17 +117,21 @@ const MAX_POPULATION_DENSITY: PopulationDensity =
 mod tests {
     use super::*;
 
+    const YEAR_FRACTION: f64 = 5.0 / 365.25;
+
     #[test]
     fn get_population_multiplier_satiation() {
         let a = get_population_multiplier(
             Satiation::new(1.0),
             Area::in_square_km(1.0),
             Population::in_millions(0.001),
+            YEAR_FRACTION,
         );
         let b = get_population_multiplier(
             Satiation::new(0.9),
             Area::in_square_km(1.0),
             Population::in_millions(0.001),
+            YEAR_FRACTION,
         );
 
         assert!(a > b);
@@ -127,11 +143,13 @@ mod tests {
             Satiation::new(1.0),
             Area::in_square_km(1.0),
             Population::in_millions(0.001),
+            YEAR_FRACTION,
         );
         let b = get_population_multiplier(
             Satiation::new(1.0),
             Area::in_square_km(0.5),
             Population::in_millions(0.001),
+            YEAR_FRACTION,
         );
 
         assert!(a > b);
@@ -143,11 +161,13 @@ mod tests {
             Satiation::new(1.0),
             Area::in_square_km(1.0),
             Population::in_millions(0.001),
+            YEAR_FRACTION,
         );
         let b = get_population_multiplier(
             Satiation::new(1.0),
             Area::in_square_km(1.0),
             Population::in_millions(0.002),
+            YEAR_FRACTION,
         );
 
         assert!(a > b);
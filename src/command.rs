@@ -0,0 +1,224 @@
+use crate::action::ColonyBodyAction;
+use crate::body::Body;
+use crate::colony::economy::ProductionUnit;
+use crate::colony::{Colony, ColonyLinks};
+use crate::components::{Facility, Price, Resource};
+use crate::ships::freighter_assignment::Assignment;
+use crate::ships::{Freighter, FreighterLinks};
+use crate::state::State;
+use crate::time::DateTime;
+use gen_id::{Id, Valid};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A one-off change to the state, scheduled on the `CommandCalendar`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Command {
+    FoundColony {
+        colony: Colony,
+        body: Id<Body>,
+    },
+    ColonyBody {
+        colony: Id<Colony>,
+        body: Id<Body>,
+        action: ColonyBodyAction,
+    },
+    CreateFreighter {
+        freighter: Freighter,
+        location: Id<Colony>,
+    },
+    /// Ignored if the freighter no longer exists
+    AssignFreighter {
+        freighter: Id<Freighter>,
+        assignment: Option<Assignment>,
+    },
+    InsertProduction {
+        colony: Id<Colony>,
+        facility: Facility,
+        unit: ProductionUnit,
+    },
+    SetPrice {
+        colony: Id<Colony>,
+        resource: Resource,
+        price: Price,
+    },
+}
+
+/// The entity created by a command
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Created {
+    Colony(Id<Colony>),
+    Freighter(Id<Freighter>),
+}
+
+impl Command {
+    pub fn execute(self, state: &mut State) -> Option<Created> {
+        match self {
+            Command::FoundColony { colony, body } => {
+                let colony = state.colony.create(colony, ColonyLinks { body });
+                Some(Created::Colony(colony))
+            }
+            Command::ColonyBody {
+                colony,
+                body,
+                action,
+            } => {
+                action.execute(Valid::assert(colony), body, state);
+                None
+            }
+            Command::CreateFreighter {
+                freighter,
+                location,
+            } => {
+                let freighter = state
+                    .freighter
+                    .create(freighter, FreighterLinks { location });
+                Some(Created::Freighter(freighter))
+            }
+            Command::AssignFreighter {
+                freighter,
+                assignment,
+            } => {
+                if let Some(freighter) = state.freighter.alloc.validate(freighter) {
+                    state.freighter.assignment.insert(freighter, assignment);
+                }
+                None
+            }
+            Command::InsertProduction {
+                colony,
+                facility,
+                unit,
+            } => {
+                state
+                    .colony
+                    .production
+                    .get_mut(facility)
+                    .insert(colony, unit);
+                None
+            }
+            Command::SetPrice {
+                colony,
+                resource,
+                price,
+            } => {
+                state
+                    .colony
+                    .resources
+                    .price
+                    .get_mut(resource)
+                    .insert(colony, price);
+                None
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct CommandId(u64);
+
+/// Commands waiting to be run by the `SystemQueue` at their scheduled date.
+///
+/// Commands scheduled for the same date run in the order they were scheduled.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CommandCalendar {
+    next_id: u64,
+    commands: BTreeMap<(DateTime, CommandId), Command>,
+}
+
+impl CommandCalendar {
+    pub fn schedule(&mut self, date: DateTime, command: Command) -> CommandId {
+        let id = CommandId(self.next_id);
+        self.next_id += 1;
+
+        self.commands.insert((date, id), command);
+
+        id
+    }
+
+    /// Removes a pending command, returning `None` if it has already run or been cancelled
+    pub fn cancel(&mut self, id: CommandId) -> Option<Command> {
+        let key = self.commands.keys().find(|(_, key)| *key == id).copied()?;
+        self.commands.remove(&key)
+    }
+
+    /// The pending commands in the order that they will run
+    pub fn get_pending(&self) -> impl Iterator<Item = (CommandId, DateTime, &Command)> + '_ {
+        self.commands
+            .iter()
+            .map(|((date, id), command)| (*id, *date, command))
+    }
+
+    pub fn get_next_date(&self) -> Option<DateTime> {
+        self.commands.keys().next().map(|(date, _)| *date)
+    }
+
+    pub fn pop(&mut self) -> Option<(DateTime, Command)> {
+        let key = *self.commands.keys().next()?;
+        self.commands.remove(&key).map(|command| (key.0, command))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::*;
+    use crate::star::examples::sol_system;
+    use crate::system_state::SystemState;
+    use crate::time::{get_date, ChronoDuration};
+    use iter_context::ContextualIterator;
+
+    fn mars_colony(state: &SystemState) -> Command {
+        Command::FoundColony {
+            colony: Colony {
+                name: "Mars Colony".to_string(),
+                population: Population::zero(),
+            },
+            body: state.state.body.get_by_name("Mars").unwrap(),
+        }
+    }
+
+    fn has_mars_colony(state: &SystemState) -> bool {
+        state
+            .state
+            .colony
+            .name
+            .iter()
+            .into_iter()
+            .any(|name| name == "Mars Colony")
+    }
+
+    #[test]
+    fn commands_run_at_scheduled_date() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+        state.state.create_star_system(sol_system());
+
+        let date = get_date(2050, 3, 1) + ChronoDuration::hours(6);
+        state.schedule(date, mars_colony(&state));
+
+        state.update(date - ChronoDuration::seconds(1)).unwrap();
+        assert!(!has_mars_colony(&state));
+
+        state.update(date + ChronoDuration::seconds(1)).unwrap();
+        assert!(has_mars_colony(&state));
+        assert_eq!(0, state.systems.commands.get_pending().count());
+    }
+
+    #[test]
+    fn cancelled_commands_do_not_run() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+        state.state.create_star_system(sol_system());
+
+        let date = get_date(2050, 3, 1);
+        let id = state.schedule(date, mars_colony(&state));
+        assert_eq!(
+            Some(id),
+            state.systems.commands.get_pending().map(|c| c.0).next()
+        );
+
+        assert!(state.cancel(id).is_some());
+        assert!(state.cancel(id).is_none());
+
+        state.update_by(90.0 * DAY).unwrap();
+        assert!(!has_mars_colony(&state));
+    }
+}
@@ -1,5 +1,5 @@
 use super::*;
-use crate::body::{BodyProperties, Habitability};
+use crate::body::Habitability;
 
 array_enum! {
     enum Resource {
@@ -34,9 +34,9 @@ impl<ID, T: Default> ResourceComponent<ID, T> {
 }
 
 impl Resource {
-    pub fn get_production_cost(&self, location: &BodyProperties) -> Price {
+    pub fn get_production_cost(&self, location: Habitability) -> Price {
         match self {
-            Food => match location.get_habitability() {
+            Food => match location {
                 Habitability::Uninhabitable => Price::in_credits_per_kg(8.0),
                 Habitability::Hostile => Price::in_credits_per_kg(4.0),
                 Habitability::Marginal => Price::in_credits_per_kg(1.0),
@@ -0,0 +1,14 @@
+use super::{Area, Power};
+
+scalar! {
+    struct Flux(f64) {
+        fn in_w_per_m2(watts_per_square_meter) -> Self;
+    }
+}
+
+scalar_div!(Power | Area = Flux);
+
+impl Flux {
+    /// The mean flux received by Earth from the Sun
+    pub const SOLAR_CONSTANT: Flux = Flux::in_w_per_m2(1361.0);
+}
@@ -1,8 +1,10 @@
 use super::*;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
+#[serde(from = "f64", into = "f64")]
 pub struct Fraction(f64);
 
 impl Eq for Fraction {}
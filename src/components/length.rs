@@ -33,7 +33,12 @@ impl Distance {
     pub fn from_angle_and_radius(angle: super::Angle, magnitude: Length) -> Self {
         let x = magnitude * angle.sin();
         let y = magnitude * angle.cos();
-        Self { x, y }
+        Self {
+            x,
+            y,
+            #[cfg(feature = "three_dimensional")]
+            z: Length::zero(),
+        }
     }
 }
 
@@ -53,3 +58,10 @@ fn orbit_radius() {
     let actual = Length::of_orbit(Mass::in_kg(100.0), Duration::in_hours(24.0));
     assert!((expected - actual).abs().value < 0.0001);
 }
+
+#[test]
+#[cfg(feature = "three_dimensional")]
+fn magnitude_includes_z() {
+    let dist = Distance::in_m_3d(2.0, 3.0, 6.0);
+    assert_eq!(Length::in_m(7.0), dist.magnitude());
+}
@@ -9,6 +9,7 @@ pub use self::area::*;
 pub use self::economy::*;
 pub use self::ema::*;
 pub use self::energy::*;
+pub use self::flux::*;
 pub use self::force::*;
 pub use self::fraction::*;
 pub use self::length::*;
@@ -29,6 +30,7 @@ mod area;
 mod economy;
 mod ema;
 mod energy;
+mod flux;
 mod force;
 mod fraction;
 mod length;
@@ -1,52 +1,140 @@
 use super::*;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct BodyOrbit {
-    pub params: Orbit,
-    pub parent: Option<Orbit>,
-}
-
-impl BodyOrbit {
-    pub fn calculate_position(&self, time: TimeFloat) -> Position {
-        if let Some(parent) = self.parent {
-            parent.calculate_position(time) + self.params.calculate_position(time)
-        } else {
-            self.params.calculate_position(time)
-        }
-        .into()
-    }
-}
-
-#[derive(Debug, Copy, Clone, PartialEq)]
+/// A Keplerian orbit around a parent body, in the plane of the star system unless inclined
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Orbit {
+    /// The semi-major axis, which is the radius of a circular orbit
     pub radius: Length,
+    /// The mean angular speed over a full period
     pub angular_speed: AngularSpeed,
+    /// Subtracted from the mean anomaly, which is zero at periapsis
     pub offset: Angle,
+    /// Zero for a circular orbit, approaching one for long, narrow orbits such as those of comets
+    #[serde(default)]
+    pub eccentricity: f64,
+    /// The argument of periapsis, the angle from the ascending node to the closest approach
+    #[serde(default)]
+    pub periapsis: Angle,
+    /// The tilt of the orbit relative to the plane of the star system
+    #[serde(default)]
+    pub inclination: Angle,
+    /// The longitude of the ascending node, where the orbit rises through the plane
+    #[serde(default)]
+    pub ascending_node: Angle,
 }
 
 impl Orbit {
+    /// A circular orbit
     pub fn from_period(radius: Length, period: Duration, offset: Angle) -> Self {
         Self {
             radius,
             angular_speed: Angle::TWO_PI / period,
             offset,
+            eccentricity: 0.0,
+            periapsis: Angle::default(),
+            inclination: Angle::default(),
+            ascending_node: Angle::default(),
         }
     }
 
     pub fn calculate_position(&self, time: TimeFloat) -> Distance {
-        let angle = self.get_angle(time);
-        Distance::from_angle_and_radius(angle, self.radius)
+        let eccentric_anomaly = self.get_eccentric_anomaly(time);
+        let angle = self.get_true_anomaly(eccentric_anomaly) + self.periapsis;
+        let radius = self.radius * (1.0 - self.eccentricity * eccentric_anomaly.cos());
+
+        // in the plane of the orbit, with the ascending node along the y-axis
+        let position = Distance::from_angle_and_radius(angle, radius);
+
+        self.rotate_from_orbital_plane(position)
     }
 
+    /// Tilts the orbit about the line of nodes, then turns it to the ascending node.
+    ///
+    /// Without the `three_dimensional` feature, the height above the plane is dropped.
+    fn rotate_from_orbital_plane(&self, position: Distance) -> Distance {
+        let inclined_x = position.x * self.inclination.cos();
+
+        let (sin, cos) = (self.ascending_node.sin(), self.ascending_node.cos());
+
+        Distance {
+            x: inclined_x * cos + position.y * sin,
+            y: position.y * cos - inclined_x * sin,
+            #[cfg(feature = "three_dimensional")]
+            z: position.x * self.inclination.sin(),
+        }
+    }
+
+    /// The mean anomaly, which increases at a constant rate
     pub fn get_angle(&self, time: TimeFloat) -> Angle {
         time.value * self.angular_speed - self.offset
     }
 
-    pub fn calculate_speed(&self) -> Speed {
-        Speed::in_m_per_s(self.radius.value() * self.angular_speed.value())
+    fn get_eccentric_anomaly(&self, time: TimeFloat) -> Angle {
+        solve_kepler(self.get_angle(time), self.eccentricity)
+    }
+
+    fn get_true_anomaly(&self, eccentric_anomaly: Angle) -> Angle {
+        let half = 0.5 * eccentric_anomaly.value();
+        let y = (1.0 + self.eccentricity).sqrt() * half.sin();
+        let x = (1.0 - self.eccentricity).sqrt() * half.cos();
+
+        Angle::in_rad(2.0 * y.atan2(x))
+    }
+
+    /// The distance from the parent at the given time
+    pub fn get_distance(&self, time: TimeFloat) -> Length {
+        let eccentric_anomaly = self.get_eccentric_anomaly(time);
+        self.radius * (1.0 - self.eccentricity * eccentric_anomaly.cos())
+    }
+
+    pub fn get_periapsis_distance(&self) -> Length {
+        self.radius * (1.0 - self.eccentricity)
+    }
+
+    pub fn get_apoapsis_distance(&self) -> Length {
+        self.radius * (1.0 + self.eccentricity)
+    }
+
+    /// The orbital speed at the given time, from the vis-viva equation
+    pub fn calculate_speed(&self, time: TimeFloat) -> Speed {
+        let a = self.radius.value();
+        let r = self.get_distance(time).value();
+        let n = self.angular_speed.value();
+
+        // the standard gravitational parameter of the parent, from Kepler's third law
+        let mu = n * n * a * a * a;
+
+        Speed::in_m_per_s((mu * (2.0 / r - 1.0 / a)).sqrt())
     }
 }
 
+/// Solves Kepler's equation, `M = E - e sin E`, for the eccentric anomaly `E` using Newton's method
+pub fn solve_kepler(mean_anomaly: Angle, eccentricity: f64) -> Angle {
+    const TOLERANCE: f64 = 1e-12;
+    const MAX_ITERATIONS: usize = 32;
+
+    // wrapped to [-π, π) so that the initial guess is close
+    let m = (mean_anomaly.value() + PI).rem_euclid(2.0 * PI) - PI;
+    let e = eccentricity;
+
+    // highly eccentric orbits converge poorly when starting from the mean anomaly
+    let mut ecc_anomaly = if e < 0.8 { m } else { PI.copysign(m) };
+
+    for _ in 0..MAX_ITERATIONS {
+        let f = ecc_anomaly - e * ecc_anomaly.sin() - m;
+        let step = f / (1.0 - e * ecc_anomaly.cos());
+        ecc_anomaly -= step;
+
+        if step.abs() < TOLERANCE {
+            break;
+        }
+    }
+
+    Angle::in_rad(ecc_anomaly)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,23 +181,94 @@ mod tests {
         assert!(nearly_zero(quarter.y));
     }
 
+    fn get_eccentric_orbit() -> Orbit {
+        Orbit {
+            eccentricity: 0.5,
+            periapsis: Angle::in_rad(PI / 2.0),
+            ..get_planet_orbit()
+        }
+    }
+
+    #[test]
+    fn kepler_solver_satisfies_equation() {
+        for eccentricity in [0.0, 0.2, 0.6, 0.9, 0.99].iter() {
+            for mean_anomaly in [-3.0, -1.0, 0.0, 0.5, 2.0, 3.1].iter() {
+                let e = solve_kepler(Angle::in_rad(*mean_anomaly), *eccentricity).value();
+                let m = e - eccentricity * e.sin();
+
+                assert!((m - mean_anomaly).abs() < 1e-9, "e: {}", eccentricity);
+            }
+        }
+    }
+
     #[test]
-    fn moon_orbit_test() {
-        let orbit = get_moon_orbit();
+    fn eccentric_orbit_starts_at_periapsis() {
+        let orbit = get_eccentric_orbit();
         let time = TimeFloat::in_s(0.0);
 
-        let moon_position = orbit.calculate_position(time);
+        let position = orbit.calculate_position(time);
+
+        assert!(nearly_zero(position.x - Length::in_m(500.0)));
+        assert!(nearly_zero(position.y));
+    }
+
+    #[test]
+    fn eccentric_orbit_reaches_apoapsis_at_half_period() {
+        let orbit = get_eccentric_orbit();
+        let time = TimeFloat::in_s(30.0);
+
+        let position = orbit.calculate_position(time);
+
+        assert!(nearly_zero(position.x + orbit.get_apoapsis_distance()));
+        assert!(nearly_zero(position.y));
+    }
+
+    #[test]
+    fn inclined_orbit_rises_above_the_plane() {
+        let orbit = Orbit {
+            inclination: Angle::in_deg(30.0),
+            ascending_node: Angle::in_rad(PI / 2.0),
+            ..get_planet_orbit()
+        };
+
+        // a quarter orbit past the ascending node is the highest point
+        let position = orbit.calculate_position(TimeFloat::in_s(15.0));
 
-        assert_eq!(Position::in_m(0.0, 1010.0), moon_position);
+        assert!(nearly_zero(position.x));
+        assert!(nearly_zero(
+            position.y + Length::in_m(1000.0 * 30f64.to_radians().cos())
+        ));
+
+        #[cfg(feature = "three_dimensional")]
+        {
+            assert!(nearly_zero(position.z - Length::in_m(500.0)));
+            assert!(nearly_zero(position.magnitude() - Length::in_m(1000.0)));
+        }
     }
 
-    fn get_moon_orbit() -> BodyOrbit {
-        BodyOrbit {
-            params: Orbit::from_period(Length::in_m(10.0), Duration::in_s(10.0), Angle::default()),
-            parent: Some(get_planet_orbit()),
+    #[test]
+    fn circular_orbit_speed_is_constant() {
+        let orbit = get_planet_orbit();
+        let expected = Speed::in_m_per_s(1000.0 * 2.0 * PI / 60.0);
+
+        for t in [0.0, 10.0, 25.0].iter() {
+            let speed = orbit.calculate_speed(TimeFloat::in_s(*t));
+            assert!((speed - expected).value().abs() < 1e-9);
         }
     }
 
+    #[test]
+    fn eccentric_orbit_is_fastest_at_periapsis() {
+        let orbit = get_eccentric_orbit();
+
+        let periapsis = orbit.calculate_speed(TimeFloat::in_s(0.0));
+        let apoapsis = orbit.calculate_speed(TimeFloat::in_s(30.0));
+
+        // conservation of angular momentum
+        let ratio = orbit.get_apoapsis_distance() / orbit.get_periapsis_distance();
+        assert!((periapsis / apoapsis - ratio).abs() < 1e-9);
+    }
+
     fn nearly_zero(value: Length) -> bool {
         Length::in_m(0.00001) > value && value > Length::in_m(-0.00001)
     }
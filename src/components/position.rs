@@ -1,26 +1,35 @@
 use super::*;
+use serde::{Deserialize, Serialize};
 
-// TODO create GalaxyPosition, GalaxyDistance to prevent comparing positions in different star systems
-
-#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+/// A position relative to a star.
+///
+/// Positions in different star systems cannot be compared. Use `GalaxyPosition::from_local` to
+/// place them in a common frame.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     pub x: Length,
     pub y: Length,
+    /// Zero for positions in the plane
+    #[cfg(feature = "three_dimensional")]
+    #[serde(default)]
+    pub z: Length,
 }
 
 impl Position {
     pub const fn in_m(x: f64, y: f64) -> Self {
+        Self::in_m_3d(x, y, 0.0)
+    }
+
+    /// The z coordinate is ignored without the `three_dimensional` feature
+    #[cfg_attr(not(feature = "three_dimensional"), allow(unused_variables))]
+    pub const fn in_m_3d(x: f64, y: f64, z: f64) -> Self {
         Self {
             x: Length::in_m(x),
             y: Length::in_m(y),
+            #[cfg(feature = "three_dimensional")]
+            z: Length::in_m(z),
         }
     }
-
-    pub const fn in_ly(x: f64, y: f64) -> Self {
-        Self::in_m(x * Self::M_PER_LY, y * Self::M_PER_LY)
-    }
-
-    const M_PER_LY: f64 = 9.460_730_472_580_8e15;
 }
 
 #[rustfmt::skip]
@@ -29,6 +38,8 @@ impl const From<Distance> for Position {
         Self {
             x: value.x,
             y: value.y,
+            #[cfg(feature = "three_dimensional")]
+            z: value.z,
         }
     }
 }
@@ -39,6 +50,8 @@ impl Add<Distance> for Position {
         Self {
             x: self.x + rhs.x,
             y: self.y + rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z + rhs.z,
         }
     }
 }
@@ -49,6 +62,8 @@ impl Add<&Distance> for Position {
         Self {
             x: self.x + rhs.x,
             y: self.y + rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z + rhs.z,
         }
     }
 }
@@ -57,6 +72,8 @@ impl AddAssign<Distance> for Position {
     fn add_assign(&mut self, rhs: Distance) {
         self.x += rhs.x;
         self.y += rhs.y;
+        #[cfg(feature = "three_dimensional")]
+        self.z += rhs.z;
     }
 }
 
@@ -64,6 +81,8 @@ impl AddAssign<&Distance> for Position {
     fn add_assign(&mut self, rhs: &Distance) {
         self.x += rhs.x;
         self.y += rhs.y;
+        #[cfg(feature = "three_dimensional")]
+        self.z += rhs.z;
     }
 }
 
@@ -73,6 +92,8 @@ impl Sub<Distance> for Position {
         Self {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
         }
     }
 }
@@ -83,6 +104,8 @@ impl Sub<&Distance> for Position {
         Self {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
         }
     }
 }
@@ -93,6 +116,8 @@ impl Sub<Distance> for &Position {
         Self::Output {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
         }
     }
 }
@@ -103,6 +128,8 @@ impl Sub<&Distance> for &Position {
         Self::Output {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
         }
     }
 }
@@ -111,6 +138,8 @@ impl SubAssign<Distance> for Position {
     fn sub_assign(&mut self, rhs: Distance) {
         self.x -= rhs.x;
         self.y -= rhs.y;
+        #[cfg(feature = "three_dimensional")]
+        self.z -= rhs.z;
     }
 }
 
@@ -118,6 +147,8 @@ impl SubAssign<&Distance> for Position {
     fn sub_assign(&mut self, rhs: &Distance) {
         self.x -= rhs.x;
         self.y -= rhs.y;
+        #[cfg(feature = "three_dimensional")]
+        self.z -= rhs.z;
     }
 }
 
@@ -127,6 +158,8 @@ impl Sub for Position {
         Distance {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
         }
     }
 }
@@ -137,6 +170,8 @@ impl Sub<&Position> for Position {
         Distance {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
         }
     }
 }
@@ -147,6 +182,8 @@ impl Sub<Position> for &Position {
         Distance {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
         }
     }
 }
@@ -157,6 +194,133 @@ impl Sub<&Position> for &Position {
         Distance {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+/// A position relative to the galactic origin, such as that of a star
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GalaxyPosition {
+    pub x: Length,
+    pub y: Length,
+    /// Zero for positions in the galactic plane
+    #[cfg(feature = "three_dimensional")]
+    #[serde(default)]
+    pub z: Length,
+}
+
+vector! {
+    struct GalaxyDistance([Length; 2]) {
+        fn in_m(meters: f64) -> Self;
+    }
+}
+
+impl GalaxyPosition {
+    pub const fn in_m(x: f64, y: f64) -> Self {
+        Self::in_m_3d(x, y, 0.0)
+    }
+
+    /// The z coordinate is ignored without the `three_dimensional` feature
+    #[cfg_attr(not(feature = "three_dimensional"), allow(unused_variables))]
+    pub const fn in_m_3d(x: f64, y: f64, z: f64) -> Self {
+        Self {
+            x: Length::in_m(x),
+            y: Length::in_m(y),
+            #[cfg(feature = "three_dimensional")]
+            z: Length::in_m(z),
+        }
+    }
+
+    pub const fn in_ly(x: f64, y: f64) -> Self {
+        Self::in_ly_3d(x, y, 0.0)
+    }
+
+    /// The z coordinate is ignored without the `three_dimensional` feature
+    pub const fn in_ly_3d(x: f64, y: f64, z: f64) -> Self {
+        Self::in_m_3d(x * Self::M_PER_LY, y * Self::M_PER_LY, z * Self::M_PER_LY)
+    }
+
+    const M_PER_LY: f64 = 9.460_730_472_580_8e15;
+
+    /// Places a position within a star system into the galaxy, given the position of its star
+    pub fn from_local(star: GalaxyPosition, local: Position) -> Self {
+        Self {
+            x: star.x + local.x,
+            y: star.y + local.y,
+            #[cfg(feature = "three_dimensional")]
+            z: star.z + local.z,
+        }
+    }
+
+    /// The position relative to a star, which is only meaningful near that star
+    pub fn to_local(self, star: GalaxyPosition) -> Position {
+        Position {
+            x: self.x - star.x,
+            y: self.y - star.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - star.z,
+        }
+    }
+}
+
+impl GalaxyDistance {
+    /// The same displacement within a single star system
+    pub fn from_local(distance: Distance) -> Self {
+        Self {
+            x: distance.x,
+            y: distance.y,
+            #[cfg(feature = "three_dimensional")]
+            z: distance.z,
+        }
+    }
+}
+
+impl Add<GalaxyDistance> for GalaxyPosition {
+    type Output = GalaxyPosition;
+    fn add(self, rhs: GalaxyDistance) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl Sub<GalaxyDistance> for GalaxyPosition {
+    type Output = GalaxyPosition;
+    fn sub(self, rhs: GalaxyDistance) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
         }
     }
 }
+
+impl Sub for GalaxyPosition {
+    type Output = GalaxyDistance;
+    fn sub(self, rhs: GalaxyPosition) -> GalaxyDistance {
+        GalaxyDistance {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            #[cfg(feature = "three_dimensional")]
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+#[test]
+fn local_positions_round_trip_through_the_galaxy() {
+    let star = GalaxyPosition::in_ly_3d(4.2, -1.0, 0.5);
+    let local = Position::in_m_3d(1.5e11, 2.0e10, -3.0e9);
+
+    let galaxy = GalaxyPosition::from_local(star, local);
+
+    // a few meters are lost to rounding at interstellar distances
+    let error = (galaxy.to_local(star) - local).magnitude();
+    assert!(error < Length::in_m(10.0));
+}
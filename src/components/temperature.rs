@@ -1,3 +1,6 @@
+use super::Flux;
+use crate::constants::STEFAN_BOLTZMANN;
+
 pub const K: Temperature = Temperature::in_k(1.0);
 
 scalar! {
@@ -5,3 +8,17 @@ scalar! {
         fn in_k(kelvin) -> Self;
     }
 }
+
+impl Temperature {
+    /// The temperature at which a body radiates as much as it absorbs, averaged over its surface
+    pub fn of_equilibrium(flux: Flux, albedo: f64) -> Self {
+        let kelvin = ((1.0 - albedo) * flux.value / (4.0 * STEFAN_BOLTZMANN)).powf(0.25);
+        Self::in_k(kelvin)
+    }
+}
+
+#[test]
+fn earth_equilibrium_temperature() {
+    let temperature = Temperature::of_equilibrium(Flux::SOLAR_CONSTANT, 0.3);
+    assert!((temperature.value - 254.6).abs() < 0.5);
+}
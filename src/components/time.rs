@@ -1,5 +1,6 @@
 use super::*;
 use crate::time::{ChronoDuration, StdDuration};
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::f64::consts::PI;
 
@@ -10,7 +11,7 @@ pub const DAY: Duration = Duration::in_hours(24.0);
 pub const YR: Duration = Duration::in_days(365.25);
 
 /// Elapsed game time in seconds.
-#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TimeFloat {
     pub value: Duration,
 }
@@ -109,6 +110,10 @@ impl Duration {
         Days(*self)
     }
 
+    pub fn as_year_fraction(self) -> f64 {
+        self / YR
+    }
+
     pub const SECONDS_PER_DAY: f64 = Self::SECONDS_PER_HOUR * 24.0;
 
     pub const SECONDS_PER_HOUR: f64 = 3600.0;
@@ -130,6 +135,12 @@ impl From<Duration> for ChronoDuration {
     }
 }
 
+impl From<StdDuration> for Duration {
+    fn from(duration: StdDuration) -> Self {
+        Duration::in_s(duration.as_secs_f64())
+    }
+}
+
 impl From<Duration> for StdDuration {
     fn from(duration: Duration) -> Self {
         let microseconds = (duration.value * 1e6) as u64;
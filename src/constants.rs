@@ -1 +1,4 @@
 pub const G: f64 = 6.6743015e-11;
+
+/// W m^-2 K^-4
+pub const STEFAN_BOLTZMANN: f64 = 5.670_374_419e-8;
@@ -53,10 +53,15 @@ impl<'a> Entity<'a, Star> {
             .map(move |b| self.state.get(b))
     }
 
-    pub fn position(&self) -> Position {
+    pub fn position(&self) -> GalaxyPosition {
         *self.state.star.position.get(self.id)
     }
 
+    /// Stars do not move, but this matches the other entities for rendering
+    pub fn position_at_time(&self, _time: TimeFloat) -> GalaxyPosition {
+        self.position()
+    }
+
     pub fn radius(&self) -> Length {
         self.state.star.star_type.get(self.id).get_radius()
     }
@@ -75,16 +80,27 @@ impl<'a> Entity<'a, Body> {
         *self.state.body.radius.get(self.id)
     }
 
-    pub fn orbit(&self) -> &BodyOrbit {
+    pub fn orbit(&self) -> &Orbit {
         self.state.body.orbit.get(self.id)
     }
 
     pub fn position(&self) -> Position {
-        self.orbit().calculate_position(self.state.time.get_time())
+        self.position_at_time(self.state.time.get_time())
     }
 
+    /// The time need not match the state, e.g., when rendering between updates
     pub fn position_at_time(&self, time: TimeFloat) -> Position {
-        self.orbit().calculate_position(time)
+        self.state.body.get_position(self.id, time)
+    }
+
+    pub fn galaxy_position(&self) -> GalaxyPosition {
+        self.galaxy_position_at_time(self.state.time.get_time())
+    }
+
+    pub fn galaxy_position_at_time(&self, time: TimeFloat) -> GalaxyPosition {
+        self.state
+            .body
+            .get_galaxy_position(self.id, time, &self.state.star)
     }
 
     pub fn properties(&self) -> BodyProperties {
@@ -95,6 +111,11 @@ impl<'a> Entity<'a, Body> {
         self.state.get(self.state.body.star.get(self.id))
     }
 
+    pub fn parent(&self) -> Option<Entity<'a, Body>> {
+        let parent = self.state.body.get_parent(self.id)?;
+        Some(self.state.get(parent))
+    }
+
     pub fn colonies(&self) -> impl Iterator<Item = Entity<Colony>> {
         let body = self.state.colony.body.iter();
         let colony = self.state.colony.alloc.ids();
@@ -148,20 +169,29 @@ impl<'a> Entity<'a, Freighter> {
         self.state.freighter.name.get(self.id)
     }
 
-    pub fn position(&self) -> Position {
+    pub fn position(&self) -> GalaxyPosition {
+        self.position_at_time(self.state.time.get_time())
+    }
+
+    /// The position at any time, without updating the state.
+    ///
+    /// Docked freighters follow their colony's body. Moving freighters travel in a straight line
+    /// from where the source body was at departure to where the destination will be on arrival,
+    /// which may be in another star system.
+    pub fn position_at_time(&self, time: TimeFloat) -> GalaxyPosition {
         let state = &self.state.freighter.state;
         match self.state.freighter.state.indices().get(self.id) {
             FreighterStateIndex::Idle(index) => {
                 let colony = state.idle.location.get(index);
-                self.state.get(colony).body().position()
+                self.state.get(colony).body().galaxy_position_at_time(time)
             }
             FreighterStateIndex::Loading(index) => {
                 let colony = state.loading.location.get(index);
-                self.state.get(colony).body().position()
+                self.state.get(colony).body().galaxy_position_at_time(time)
             }
             FreighterStateIndex::Unloading(index) => {
                 let colony = state.unloading.location.get(index);
-                self.state.get(colony).body().position()
+                self.state.get(colony).body().galaxy_position_at_time(time)
             }
             FreighterStateIndex::Moving(index) => {
                 let source = state.moving.source.get(index);
@@ -169,15 +199,72 @@ impl<'a> Entity<'a, Freighter> {
 
                 let departure = *state.moving.departure.get(index).unwrap();
                 let arrival = *state.moving.arrival.get(index).unwrap();
-                let time = self.state.time.get_time();
 
-                let departure_pos = self.state.get(source).body().position_at_time(departure);
-                let arrival_pos = self.state.get(destination).body().position_at_time(arrival);
-
-                let fraction = Fraction::clamp((time - departure) / (arrival - departure));
+                let departure_pos = self
+                    .state
+                    .get(source)
+                    .body()
+                    .galaxy_position_at_time(departure);
+                let arrival_pos = self
+                    .state
+                    .get(destination)
+                    .body()
+                    .galaxy_position_at_time(arrival);
+
+                let fraction = state.moving.get_trip_fraction_at(index, time).unwrap();
                 let trip_vector = arrival_pos - departure_pos;
                 departure_pos + trip_vector * fraction.value()
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_state::examples::shipping_route_from_mars;
+
+    #[test]
+    fn freighter_positions_are_interpolated_along_the_trip() {
+        let (mut state, freighter) = shipping_route_from_mars();
+        let mars = state.state.body.get_by_name("Mars").unwrap();
+
+        // the first update sends the idle freighter from Mars to the nearest colony on its route
+        state.update_by(Duration::in_s(1.0)).unwrap();
+
+        let now = state.state.time.get_time();
+        let entity = state.state.get(Valid::assert(freighter));
+
+        let freighter_state = &state.state.freighter.state;
+        let (departure, arrival, destination) = match freighter_state.indices().get(entity.id) {
+            FreighterStateIndex::Moving(index) => (
+                *freighter_state.moving.departure.get(index).unwrap(),
+                *freighter_state.moving.arrival.get(index).unwrap(),
+                *freighter_state.moving.destination.get(index),
+            ),
+            _ => panic!("freighter should be moving"),
+        };
+
+        let at = |time| entity.position_at_time(time);
+        let mars_at_departure = state.state.get(mars).galaxy_position_at_time(departure);
+        let destination_at_arrival = state
+            .state
+            .get(destination)
+            .body()
+            .galaxy_position_at_time(arrival);
+        let midpoint = at(departure + (arrival - departure) * 0.5);
+
+        assert_eq!(at(now), entity.position());
+        assert!((at(departure) - mars_at_departure).magnitude() < 1.0 * KM);
+        assert!((at(arrival) - destination_at_arrival).magnitude() < 1.0 * KM);
+        assert!((at(arrival + DAY) - destination_at_arrival).magnitude() < 1.0 * KM);
+        assert!(
+            ((midpoint - mars_at_departure) - (destination_at_arrival - mars_at_departure) * 0.5)
+                .magnitude()
+                < 1.0 * KM
+        );
+
+        // querying does not advance the state
+        assert_eq!(now, state.state.time.get_time());
+    }
+}
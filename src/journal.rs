@@ -0,0 +1,256 @@
+use crate::command::{Command, CommandId};
+use crate::components::Duration;
+use crate::star::StarSystem;
+use crate::system_state::SystemState;
+use crate::systems::{CustomSystem, RegisterError, SystemKey};
+use crate::time::{DateTime, TimeError};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+
+/// An external change made through `SystemState`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JournalEntry {
+    Apply(Command),
+    Schedule(DateTime, Command),
+    Cancel(CommandId),
+    /// A registered system, by name
+    Register(String),
+    Pause(SystemKey),
+    Resume(SystemKey),
+    SetInterval(SystemKey, Duration),
+}
+
+/// Every external change made to a `SystemState`, along with the game time at which it was made.
+///
+/// Replaying the journal onto the same star systems reproduces the original state.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Journal {
+    entries: Vec<(DateTime, JournalEntry)>,
+}
+
+impl Journal {
+    pub fn record(&mut self, date_time: DateTime, entry: JournalEntry) {
+        self.entries.push((date_time, entry));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(DateTime, JournalEntry)> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), bincode::Error> {
+        bincode::serialize_into(writer, self)
+    }
+
+    pub fn read<R: Read>(reader: R) -> Result<Self, bincode::Error> {
+        bincode::deserialize_from(reader)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Time(TimeError),
+    Register(RegisterError),
+    /// A system registered in the journal was not supplied
+    MissingSystem(String),
+    /// A supplied system is not registered in the journal
+    UnexpectedSystem(String),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Time(error) => write!(f, "{}", error),
+            ReplayError::Register(error) => write!(f, "{}", error),
+            ReplayError::MissingSystem(name) => write!(f, "missing registered system: {}", name),
+            ReplayError::UnexpectedSystem(name) => {
+                write!(f, "system is not registered in the journal: {}", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Time(error) => Some(error),
+            ReplayError::Register(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<TimeError> for ReplayError {
+    fn from(error: TimeError) -> Self {
+        ReplayError::Time(error)
+    }
+}
+
+impl From<RegisterError> for ReplayError {
+    fn from(error: RegisterError) -> Self {
+        ReplayError::Register(error)
+    }
+}
+
+impl SystemState {
+    /// Rebuilds a state by creating the star systems and then repeating each journal entry at its recorded time.
+    ///
+    /// Entries are replayed after the systems due at that time, except for entries made before the first update.
+    /// `systems` must contain each system registered in the journal.
+    pub fn replay(
+        start_date: DateTime,
+        star_systems: Vec<StarSystem>,
+        mut systems: Vec<Box<dyn CustomSystem>>,
+        journal: &Journal,
+    ) -> Result<Self, ReplayError> {
+        let mut state = SystemState::new(start_date);
+
+        for star_system in star_systems {
+            state.state.create_star_system(star_system);
+        }
+
+        for (date_time, entry) in journal.iter() {
+            if *date_time > state.state.time.get_date_time() {
+                state.update(*date_time)?;
+            }
+
+            match entry.clone() {
+                JournalEntry::Apply(command) => {
+                    state.apply(command);
+                }
+                JournalEntry::Schedule(date, command) => {
+                    state.schedule(date, command);
+                }
+                JournalEntry::Cancel(id) => {
+                    state.cancel(id);
+                }
+                JournalEntry::Register(name) => {
+                    let index = systems
+                        .iter()
+                        .position(|system| system.name() == name)
+                        .ok_or(ReplayError::MissingSystem(name))?;
+
+                    state.register_boxed(systems.swap_remove(index))?;
+                }
+                JournalEntry::Pause(key) => state.pause(key),
+                JournalEntry::Resume(key) => state.resume(key)?,
+                JournalEntry::SetInterval(key, interval) => state.set_interval(key, interval)?,
+            }
+        }
+
+        if let Some(system) = systems.first() {
+            return Err(ReplayError::UnexpectedSystem(system.name().to_string()));
+        }
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::economy::ProductionUnit;
+    use crate::colony::examples::european_union;
+    use crate::components::*;
+    use crate::star::examples::sol_system;
+    use crate::state::State;
+    use crate::system_state::examples::{save, shipping_route};
+    use crate::systems::System;
+    use crate::time::get_date;
+
+    #[derive(Debug, Clone)]
+    struct Tax;
+
+    impl CustomSystem for Tax {
+        fn name(&self) -> &str {
+            "Tax"
+        }
+
+        fn get_interval(&self) -> Duration {
+            30.0 * DAY
+        }
+
+        fn run(&mut self, _state: &mut State) {}
+    }
+
+    #[test]
+    fn journal_replays_to_identical_state() {
+        let mut state = shipping_route();
+        let start = state.state.time.get_date_time();
+
+        state.update_by(10.0 * DAY).unwrap();
+
+        let earth = state.state.body.get_by_name("Earth").unwrap();
+        let colony = state.state.colony.alloc.ids().into_iter().next().unwrap();
+        let location = state.state.body.get_habitability(earth, &state.state.star);
+        let unit = ProductionUnit::new(10.0 * TON / DAY, Resource::Food, location);
+        let date = get_date(2050, 2, 1);
+        let production = Command::InsertProduction {
+            colony,
+            facility: Facility::Farmland,
+            unit,
+        };
+        state.schedule(date, production);
+
+        state.update_by(60.0 * DAY).unwrap();
+
+        let mut bytes = Vec::new();
+        state.journal.write(&mut bytes).unwrap();
+        let journal = Journal::read(bytes.as_slice()).unwrap();
+        assert_eq!(5, journal.len());
+
+        let mut replayed =
+            SystemState::replay(start, vec![sol_system()], vec![], &journal).unwrap();
+        replayed.update(state.state.time.get_date_time()).unwrap();
+
+        assert_eq!(
+            bincode::serialize(&state.state).unwrap(),
+            bincode::serialize(&replayed.state).unwrap()
+        );
+    }
+
+    #[test]
+    fn journal_replays_prices_and_schedule_changes() {
+        let start = get_date(2050, 1, 1);
+        let mut state = SystemState::new(start);
+        state.state.create_star_system(sol_system());
+
+        let earth = state.state.body.get_by_name("Earth").unwrap();
+        let colony = state.create_colony(european_union(), earth);
+        state.apply(Command::SetPrice {
+            colony,
+            resource: Resource::Food,
+            price: Resource::Food.get_default_price() * 2.0,
+        });
+        state.register(Tax).unwrap();
+
+        state.update_by(5.0 * DAY).unwrap();
+        state.pause(System::ColonyPopulation);
+        state
+            .set_interval(System::ResourceDecay, 3.0 * DAY)
+            .unwrap();
+
+        state.update_by(20.0 * DAY).unwrap();
+        state.resume(System::ColonyPopulation).unwrap();
+        state.update_by(20.0 * DAY).unwrap();
+
+        let replay = |systems: Vec<Box<dyn CustomSystem>>| {
+            SystemState::replay(start, vec![sol_system()], systems, &state.journal)
+        };
+
+        assert!(matches!(replay(vec![]), Err(ReplayError::MissingSystem(name)) if name == "Tax"));
+
+        let mut replayed = replay(vec![Box::new(Tax)]).unwrap();
+        replayed.update(state.state.time.get_date_time()).unwrap();
+
+        assert_eq!(save(&state), save(&replayed));
+    }
+}
@@ -19,17 +19,25 @@ pub mod macros;
 
 pub mod action;
 pub mod body;
+pub mod clock;
 pub mod colony;
+pub mod command;
 pub mod components;
 pub mod constants;
 pub mod construction;
 pub mod entity;
 pub mod ftl;
+pub mod journal;
+pub mod metrics;
+pub mod parallel;
 pub mod resources;
+pub mod save;
+pub mod scenario;
 pub mod ships;
 pub mod spaceport;
 pub mod star;
 pub mod state;
+pub mod stats;
 pub mod system_state;
 pub mod systems;
 pub mod time;
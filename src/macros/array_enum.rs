@@ -4,7 +4,18 @@ macro_rules! array_enum {
             $( $enum_type:ident ),+ $(,)?
         }
     ) => {
-        #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        #[derive(
+            Debug,
+            Copy,
+            Clone,
+            Eq,
+            PartialEq,
+            Ord,
+            PartialOrd,
+            Hash,
+            serde::Serialize,
+            serde::Deserialize,
+        )]
         pub enum $name {
             $(
                 $enum_type,
@@ -47,7 +58,7 @@ macro_rules! array_enum {
             }
         }
 
-        #[derive(Debug, Default, Copy, Clone)]
+        #[derive(Debug, Default, Copy, Clone, serde::Serialize, serde::Deserialize)]
         pub struct $array <T> {
             values: [T; $name::LEN],
         }
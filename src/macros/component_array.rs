@@ -1,6 +1,10 @@
 macro_rules! component_array {
     ($name:ident, $enum:ty, $array:ty) => {
-        #[derive(Debug)]
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        #[serde(bound(
+            serialize = "Component<ID, T>: serde::Serialize",
+            deserialize = "Component<ID, T>: serde::Deserialize<'de>"
+        ))]
         pub struct $name<ID, T> {
             components: [Component<ID, T>; <$enum>::LEN],
         }
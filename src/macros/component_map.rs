@@ -1,6 +1,10 @@
 macro_rules! component_map {
     ($name:ident, $enum:ty) => {
-        #[derive(Debug)]
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        #[serde(bound(
+            serialize = "IdMap<ID, T>: serde::Serialize",
+            deserialize = "IdMap<ID, T>: serde::Deserialize<'de>"
+        ))]
         pub struct $name<ID, T> {
             map: [IdMap<ID, T>; <$enum>::LEN],
         }
@@ -13,6 +17,14 @@ macro_rules! component_map {
             }
         }
 
+        impl<ID, T: Clone> Clone for $name<ID, T> {
+            fn clone(&self) -> Self {
+                Self {
+                    map: self.map.clone(),
+                }
+            }
+        }
+
         impl<ID, T> $name<ID, T> {
             pub fn get(&self, value: $enum) -> &IdMap<ID, T> {
                 &self.map[value.index()]
@@ -2,7 +2,8 @@ macro_rules! scalar {
     {
         struct $scalar:ident($base:ty)
     } => {
-        #[derive(Debug, Default, Copy, Clone)]
+        #[derive(Debug, Default, Copy, Clone, serde::Serialize, serde::Deserialize)]
+        #[serde(transparent)]
         pub struct $scalar {
             pub value: $base,
         }
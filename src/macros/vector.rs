@@ -4,10 +4,25 @@ macro_rules! vector {
             fn $in_unit:ident($unit:ident: $base:ty) -> Self;
         }
     } => {
-        #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+        #[derive(
+            Debug,
+            Default,
+            Copy,
+            Clone,
+            Eq,
+            PartialEq,
+            Ord,
+            PartialOrd,
+            serde::Serialize,
+            serde::Deserialize,
+        )]
         pub struct $vector {
             pub x: $scalar,
             pub y: $scalar,
+            /// Zero for vectors in the plane
+            #[cfg(feature = "three_dimensional")]
+            #[serde(default)]
+            pub z: $scalar,
         }
 
         impl $vector {
@@ -15,6 +30,21 @@ macro_rules! vector {
                 Self {
                     x: $scalar::new(x),
                     y: $scalar::new(y),
+                    #[cfg(feature = "three_dimensional")]
+                    z: $scalar::new(0.0),
+                }
+            }
+
+            paste::item! {
+                /// The z component is ignored without the `three_dimensional` feature
+                #[cfg_attr(not(feature = "three_dimensional"), allow(unused_variables))]
+                pub const fn [<$in_unit _3d>](x: $base, y: $base, z: $base) -> Self {
+                    Self {
+                        x: $scalar::new(x),
+                        y: $scalar::new(y),
+                        #[cfg(feature = "three_dimensional")]
+                        z: $scalar::new(z),
+                    }
                 }
             }
 
@@ -22,9 +52,17 @@ macro_rules! vector {
                 $scalar::new(self.magnitude_squared_float().sqrt())
             }
 
+            #[cfg(not(feature = "three_dimensional"))]
             fn magnitude_squared_float(self) -> $base {
                 self.x.value * self.x.value + self.y.value * self.y.value
             }
+
+            #[cfg(feature = "three_dimensional")]
+            fn magnitude_squared_float(self) -> $base {
+                self.x.value * self.x.value
+                    + self.y.value * self.y.value
+                    + self.z.value * self.z.value
+            }
         }
 
         impl const std::ops::Add<$vector> for $vector {
@@ -33,6 +71,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x + rhs.x,
                     y: self.y + rhs.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z + rhs.z,
                 }
             }
         }
@@ -43,6 +83,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x + rhs.x,
                     y: self.y + rhs.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z + rhs.z,
                 }
             }
         }
@@ -53,6 +95,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x + rhs.x,
                     y: self.y + rhs.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z + rhs.z,
                 }
             }
         }
@@ -63,6 +107,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x + rhs.x,
                     y: self.y + rhs.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z + rhs.z,
                 }
             }
         }
@@ -73,6 +119,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x - rhs.x,
                     y: self.y - rhs.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z - rhs.z,
                 }
             }
         }
@@ -83,6 +131,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x - rhs.x,
                     y: self.y - rhs.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z - rhs.z,
                 }
             }
         }
@@ -93,6 +143,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x - rhs.x,
                     y: self.y - rhs.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z - rhs.z,
                 }
             }
         }
@@ -103,6 +155,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x - rhs.x,
                     y: self.y - rhs.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z - rhs.z,
                 }
             }
         }
@@ -111,6 +165,8 @@ macro_rules! vector {
             fn add_assign(&mut self, rhs: Self) {
                 self.x += rhs.x;
                 self.y += rhs.y;
+                #[cfg(feature = "three_dimensional")]
+                self.z += rhs.z;
             }
         }
 
@@ -118,6 +174,8 @@ macro_rules! vector {
             fn add_assign(&mut self, rhs: &$vector) {
                 self.x += rhs.x;
                 self.y += rhs.y;
+                #[cfg(feature = "three_dimensional")]
+                self.z += rhs.z;
             }
         }
 
@@ -125,6 +183,8 @@ macro_rules! vector {
             fn sub_assign(&mut self, rhs: $vector) {
                 self.x -= rhs.x;
                 self.y -= rhs.y;
+                #[cfg(feature = "three_dimensional")]
+                self.z -= rhs.z;
             }
         }
 
@@ -132,6 +192,8 @@ macro_rules! vector {
             fn sub_assign(&mut self, rhs: &$vector) {
                 self.x -= rhs.x;
                 self.y -= rhs.y;
+                #[cfg(feature = "three_dimensional")]
+                self.z -= rhs.z;
             }
         }
 
@@ -141,6 +203,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x * rhs,
                     y: self.y * rhs,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z * rhs,
                 }
             }
         }
@@ -151,6 +215,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x * rhs,
                     y: self.y * rhs,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z * rhs,
                 }
             }
         }
@@ -161,6 +227,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x * *rhs,
                     y: self.y * *rhs,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z * *rhs,
                 }
             }
         }
@@ -171,6 +239,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x * *rhs,
                     y: self.y * *rhs,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z * *rhs,
                 }
             }
         }
@@ -207,6 +277,8 @@ macro_rules! vector {
             fn mul_assign(&mut self, rhs: $base) {
                 self.x *= rhs;
                 self.y *= rhs;
+                #[cfg(feature = "three_dimensional")]
+                self.z *= rhs;
             }
         }
 
@@ -222,6 +294,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x / rhs,
                     y: self.y / rhs,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z / rhs,
                 }
             }
         }
@@ -232,6 +306,8 @@ macro_rules! vector {
                 Self::Output {
                     x: self.x / rhs,
                     y: self.y / rhs,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.z / rhs,
                 }
             }
         }
@@ -254,6 +330,8 @@ macro_rules! vector {
             fn div_assign(&mut self, rhs: $base) {
                 self.x /= rhs;
                 self.y /= rhs;
+                #[cfg(feature = "three_dimensional")]
+                self.z /= rhs;
             }
         }
 
@@ -269,6 +347,8 @@ macro_rules! vector {
                 Self::Output {
                     x: -self.x,
                     y: -self.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: -self.z,
                 }
             }
         }
@@ -279,6 +359,8 @@ macro_rules! vector {
                 Self::Output {
                     x: -self.x,
                     y: -self.y,
+                    #[cfg(feature = "three_dimensional")]
+                    z: -self.z,
                 }
             }
         }
@@ -22,6 +22,8 @@ macro_rules! vector_and_scalar {
                 $vector {
                     x: self.0 * rhs,
                     y: self.1 * rhs,
+                    #[cfg(feature = "three_dimensional")]
+                    z: $scalar::new(0.0),
                 }
             }
         }
@@ -33,5 +35,26 @@ macro_rules! vector_and_scalar {
                 self * *rhs
             }
         }
+
+        impl const std::ops::Mul<$scalar> for ($base, $base, $base) {
+            type Output = $vector;
+
+            fn mul(self, rhs: $scalar) -> Self::Output {
+                $vector {
+                    x: self.0 * rhs,
+                    y: self.1 * rhs,
+                    #[cfg(feature = "three_dimensional")]
+                    z: self.2 * rhs,
+                }
+            }
+        }
+
+        impl const std::ops::Mul<&$scalar> for ($base, $base, $base) {
+            type Output = $vector;
+
+            fn mul(self, rhs: &$scalar) -> Self::Output {
+                self * *rhs
+            }
+        }
     };
 }
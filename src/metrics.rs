@@ -0,0 +1,296 @@
+use crate::colony::{Colonies, Colony};
+use crate::components::*;
+use crate::time::DateTime;
+use crate::HashMap;
+use gen_id::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter, Result};
+
+/// A per-colony value sampled by the `RecordMetrics` system
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Metric {
+    /// Credits per kg
+    Price(Resource),
+    /// Tons per day
+    Supply(Resource),
+    /// Tons per day
+    Demand(Resource),
+    /// Tons
+    Stockpile(Resource),
+    /// Millions of people
+    Population,
+    Satiation,
+    /// Tons per day, zero if the colony has no such facility
+    Capacity(Facility),
+}
+
+impl Metric {
+    pub fn sample(self, colonies: &Colonies, colony: Id<Colony>) -> f64 {
+        let resources = &colonies.resources;
+
+        match self {
+            Metric::Price(resource) => resources.price.get(resource).get(colony).value,
+            Metric::Supply(resource) => {
+                *resources.supply.get(resource).get(colony) / MassRate::in_tons_per_day(1.0)
+            }
+            Metric::Demand(resource) => {
+                *resources.demand.get(resource).get(colony) / MassRate::in_tons_per_day(1.0)
+            }
+            Metric::Stockpile(resource) => *resources.stockpile.get(resource).get(colony) / TON,
+            Metric::Population => {
+                *colonies.people.population.get(colony) / Population::in_millions(1.0)
+            }
+            Metric::Satiation => colonies.people.satiation.get(colony).value(),
+            Metric::Capacity(facility) => colonies
+                .production
+                .get(facility)
+                .get(colony)
+                .map(|unit| unit.capacity / MassRate::in_tons_per_day(1.0))
+                .unwrap_or(0.0),
+        }
+    }
+}
+
+/// Used as the CSV column header
+impl Display for Metric {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Metric::Price(resource) => write!(f, "{} price", resource),
+            Metric::Supply(resource) => write!(f, "{} supply", resource),
+            Metric::Demand(resource) => write!(f, "{} demand", resource),
+            Metric::Stockpile(resource) => write!(f, "{} stockpile", resource),
+            Metric::Population => write!(f, "Population"),
+            Metric::Satiation => write!(f, "Satiation"),
+            Metric::Capacity(facility) => write!(f, "{} capacity", facility),
+        }
+    }
+}
+
+/// A fixed-capacity queue that discards its oldest value when full
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RingBuffer<T> {
+    values: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+
+        self.values.push_back(value);
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.values.iter()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.values.get(index)
+    }
+}
+
+/// The samples of a single colony, with one buffer per recorded metric
+#[derive(Debug, Clone)]
+struct ColonySeries {
+    colony: Id<Colony>,
+    values: Vec<RingBuffer<f64>>,
+}
+
+/// Samples the chosen metrics of every colony each time the `RecordMetrics` system runs.
+///
+/// Only the most recent `capacity` samples are kept. Colonies founded after recording began
+/// have fewer samples than there are dates, and no rows in the CSV before they were founded.
+#[derive(Debug, Clone)]
+pub struct MetricsRecorder {
+    metrics: Vec<Metric>,
+    capacity: usize,
+    dates: RingBuffer<DateTime>,
+    /// In the order that colonies were first sampled
+    series: Vec<ColonySeries>,
+    series_index: HashMap<Id<Colony>, usize>,
+}
+
+impl Default for MetricsRecorder {
+    /// Records nothing
+    fn default() -> Self {
+        Self::new(Vec::new(), 0)
+    }
+}
+
+impl MetricsRecorder {
+    pub const DEFAULT_CAPACITY: usize = 1000;
+
+    pub fn new(metrics: Vec<Metric>, capacity: usize) -> Self {
+        Self {
+            metrics,
+            capacity,
+            dates: RingBuffer::new(capacity),
+            series: Vec::new(),
+            series_index: HashMap::default(),
+        }
+    }
+
+    pub fn get_metrics(&self) -> &[Metric] {
+        &self.metrics
+    }
+
+    /// True if the recorder has metrics to sample and room to keep them
+    pub fn is_enabled(&self) -> bool {
+        !self.metrics.is_empty() && self.capacity > 0
+    }
+
+    pub fn get_dates(&self) -> &RingBuffer<DateTime> {
+        &self.dates
+    }
+
+    /// The samples of a metric for a colony, aligned with the most recent dates
+    pub fn get(&self, colony: Id<Colony>, metric: Metric) -> Option<&RingBuffer<f64>> {
+        let index = self.metrics.iter().position(|m| *m == metric)?;
+
+        self.series_index
+            .get(&colony)
+            .map(|series| &self.series[*series].values[index])
+    }
+
+    pub fn record(&mut self, colonies: &Colonies, date: DateTime) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.dates.push(date);
+
+        for colony in colonies.alloc.ids() {
+            let index = self.get_series_index(colony);
+            let series = &mut self.series[index];
+
+            for (value, metric) in series.values.iter_mut().zip(&self.metrics) {
+                value.push(metric.sample(colonies, colony));
+            }
+        }
+    }
+
+    /// Adds an empty series the first time a colony is sampled
+    fn get_series_index(&mut self, colony: Id<Colony>) -> usize {
+        if let Some(index) = self.series_index.get(&colony) {
+            return *index;
+        }
+
+        let values = self
+            .metrics
+            .iter()
+            .map(|_| RingBuffer::new(self.capacity))
+            .collect();
+
+        let index = self.series.len();
+        self.series.push(ColonySeries { colony, values });
+        self.series_index.insert(colony, index);
+        index
+    }
+
+    /// One row per date and colony, with a column for each metric
+    pub fn to_csv(&self, colonies: &Colonies) -> String {
+        let mut csv = String::from("date,colony");
+        for metric in &self.metrics {
+            csv.push_str(&format!(",{}", metric));
+        }
+        csv.push('\n');
+
+        for (index, date) in self.dates.iter().enumerate() {
+            for series in &self.series {
+                // series of newer colonies are missing their oldest samples
+                let missing = self.dates.len() - series.values.first().map_or(0, RingBuffer::len);
+                if index < missing {
+                    continue;
+                }
+
+                let name = colonies.name.get(series.colony);
+
+                csv.push_str(&format!("{},{}", date.format("%Y-%m-%d %H:%M:%S"), name));
+                for values in &series.values {
+                    if let Some(value) = values.get(index - missing) {
+                        csv.push_str(&format!(",{}", value));
+                    }
+                }
+                csv.push('\n');
+            }
+        }
+
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_state::examples::shipping_route;
+    use crate::systems::System;
+
+    #[test]
+    fn ring_buffer_discards_oldest_values() {
+        let mut buffer = RingBuffer::new(2);
+        buffer.push(1);
+        buffer.push(2);
+        buffer.push(3);
+
+        assert_eq!(vec![&2, &3], buffer.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn recorder_samples_colonies_at_interval() {
+        let mut state = shipping_route();
+        let metrics = vec![
+            Metric::Population,
+            Metric::Satiation,
+            Metric::Price(Resource::Food),
+            Metric::Capacity(Facility::Farmland),
+        ];
+        state.systems.set_metrics(MetricsRecorder::new(metrics, 5));
+        state
+            .set_interval(System::RecordMetrics, 10.0 * DAY)
+            .unwrap();
+
+        state.update_by(100.0 * DAY).unwrap();
+
+        let recorder = state.systems.get_metrics();
+        assert_eq!(5, recorder.get_dates().len());
+
+        let colony = state.state.colony.alloc.ids().into_iter().next().unwrap();
+        let population = recorder.get(colony, Metric::Population).unwrap();
+        assert_eq!(5, population.len());
+
+        let csv = recorder.to_csv(&state.state.colony);
+        let mut lines = csv.lines();
+        assert_eq!(
+            Some("date,colony,Population,Satiation,Food price,Farmland capacity"),
+            lines.next()
+        );
+        assert_eq!(10, lines.count());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::colony::economy::{decay_stockpile, update_shipping_avg, Resources};
+use crate::colony::Colonies;
+use crate::components::Duration;
+use crate::state::State;
+use crate::systems::System;
+use crate::time::StdDuration;
+use rayon::prelude::*;
+use std::time::Instant;
+
+/// Parts of the `State` read or written by systems, at the granularity used to decide which
+/// systems may run concurrently
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Data {
+    Time,
+    Stars,
+    Bodies,
+    ColonyBody,
+    ColonyPeople,
+    ColonyStockpile,
+    ColonyShipping,
+    ColonyMarket,
+    ColonyProduction,
+    Freighters,
+}
+
+/// The data a system reads and writes when run
+#[derive(Debug, Copy, Clone)]
+pub struct Access {
+    pub reads: &'static [Data],
+    pub writes: &'static [Data],
+}
+
+impl Access {
+    /// True if either access writes data that the other reads or writes
+    pub fn conflicts(&self, other: &Access) -> bool {
+        let writes_to = |writes: &[Data], access: &Access| {
+            writes
+                .iter()
+                .any(|data| access.reads.contains(data) || access.writes.contains(data))
+        };
+
+        writes_to(self.writes, other) || writes_to(other.writes, self)
+    }
+}
+
+/// A system with a split implementation in `run`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ParallelSystem {
+    ColonyPopulation,
+    ResourceDecay,
+    ShippingAverage,
+}
+
+impl ParallelSystem {
+    pub const fn get_system(self) -> System {
+        match self {
+            ParallelSystem::ColonyPopulation => System::ColonyPopulation,
+            ParallelSystem::ResourceDecay => System::ResourceDecay,
+            ParallelSystem::ShippingAverage => System::ShippingAverage,
+        }
+    }
+
+    pub const fn get_access(self) -> Access {
+        self.get_system().get_access()
+    }
+}
+
+/// Systems due at the same time, none of which conflict with another
+#[derive(Debug, Default, Clone)]
+pub struct Batch {
+    systems: Vec<(ParallelSystem, Duration)>,
+}
+
+impl Batch {
+    pub fn can_add(&self, system: ParallelSystem) -> bool {
+        let access = system.get_access();
+
+        self.systems
+            .iter()
+            .all(|(other, _)| !other.get_access().conflicts(&access))
+    }
+
+    /// Panics if the system conflicts with one already in the batch
+    pub fn add(&mut self, system: ParallelSystem, interval: Duration) {
+        assert!(
+            self.can_add(system),
+            "{:?} conflicts with the systems in the batch",
+            system
+        );
+
+        self.systems.push((system, interval));
+    }
+
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(ParallelSystem, Duration)> {
+        self.systems.iter()
+    }
+}
+
+type Job<'a> = Box<dyn FnOnce() -> StdDuration + Send + 'a>;
+
+/// Runs systems that are due at the same time on separate threads, returning the time taken by each.
+///
+/// Since the systems in a batch share no mutable data, the result is the same as running them in order.
+pub fn run(batch: &Batch, state: &mut State) -> Vec<StdDuration> {
+    let Colonies {
+        people,
+        resources,
+        body: colony_body,
+        ..
+    } = &mut state.colony;
+
+    let Resources {
+        stockpile,
+        shipping,
+        avg_shipping,
+        ..
+    } = resources;
+
+    let colony_body = &*colony_body;
+    let mut population = Some((people, &mut state.body));
+    let mut stockpile = Some(stockpile);
+    let mut shipping = Some((shipping, avg_shipping));
+
+    // a batch never holds the same system twice, as every system conflicts with itself
+    let jobs = batch
+        .iter()
+        .map(|(system, interval)| {
+            let interval = *interval;
+
+            let job: Job = match system {
+                ParallelSystem::ColonyPopulation => {
+                    let (people, bodies) = population.take().expect("system run twice");
+                    Box::new(move || {
+                        timed(|| people.update_population(colony_body, bodies, interval))
+                    })
+                }
+                ParallelSystem::ResourceDecay => {
+                    let stockpile = stockpile.take().expect("system run twice");
+                    Box::new(move || timed(|| decay_stockpile(stockpile, interval)))
+                }
+                ParallelSystem::ShippingAverage => {
+                    let (shipping, avg_shipping) = shipping.take().expect("system run twice");
+                    Box::new(move || {
+                        timed(|| update_shipping_avg(shipping, avg_shipping, interval))
+                    })
+                }
+            };
+
+            job
+        })
+        .collect::<Vec<_>>();
+
+    jobs.into_par_iter().map(|job| job()).collect()
+}
+
+fn timed<F: FnOnce()>(f: F) -> StdDuration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_conflicts() {
+        let decay = System::ResourceDecay.get_access();
+        let shipping = System::ShippingAverage.get_access();
+        let population = System::ColonyPopulation.get_access();
+        let production = System::ColonyProductionCycle.get_access();
+        let freighters = System::FreighterState.get_access();
+
+        assert!(!decay.conflicts(&shipping));
+        assert!(!decay.conflicts(&population));
+        assert!(!shipping.conflicts(&population));
+
+        assert!(production.conflicts(&decay));
+        assert!(production.conflicts(&population));
+        assert!(freighters.conflicts(&population));
+    }
+
+    #[test]
+    fn parallel_systems_conflict_with_themselves() {
+        for system in System::ARRAY.iter() {
+            if let Some(system) = system.to_parallel() {
+                let mut batch = Batch::default();
+                batch.add(system, Duration::zero());
+
+                assert!(!batch.can_add(system));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn conflicting_systems_cannot_be_batched() {
+        let mut batch = Batch::default();
+        batch.add(ParallelSystem::ResourceDecay, Duration::zero());
+        batch.add(ParallelSystem::ResourceDecay, Duration::zero());
+    }
+}
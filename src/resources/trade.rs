@@ -115,8 +115,7 @@ impl Market {
 pub struct BidAsk {
     pub price: Price,
     pub amount: Mass,
-    // TODO use GalaxyPosition
-    pub location: Position,
+    pub location: GalaxyPosition,
 }
 
 #[derive(Debug, Clone)]
@@ -154,7 +153,7 @@ pub struct ResourceBuyers {
 
     pub price: IndexedMaxQueue<ResourceBuyer, Price>,
     pub amount: Component<ResourceBuyer, Mass>,
-    pub location: Component<ResourceBuyer, Position>,
+    pub location: Component<ResourceBuyer, GalaxyPosition>,
 }
 
 impl ResourceBuyers {
@@ -187,7 +186,7 @@ pub struct ResourceSellers {
 
     pub price: IndexedMinQueue<ResourceSeller, Price>,
     pub amount: Component<ResourceSeller, Mass>,
-    pub location: Component<ResourceSeller, Position>,
+    pub location: Component<ResourceSeller, GalaxyPosition>,
 }
 
 impl ResourceSellers {
@@ -0,0 +1,187 @@
+use crate::journal::Journal;
+use crate::state::State;
+use crate::system_state::SystemState;
+use crate::systems::{CustomSystem, SystemQueue, SystemQueueSave};
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+
+/// Incremented whenever the saved layout of the state changes
+pub const SAVE_VERSION: u32 = 7;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Format(bincode::Error),
+    UnsupportedVersion(u32),
+    /// A saved registered system was not supplied on load
+    MissingSystem(String),
+    /// A supplied system was not registered when the state was saved
+    UnexpectedSystem(String),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Format(error) => write!(f, "invalid save: {}", error),
+            LoadError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported save version {}, expected {}",
+                version, SAVE_VERSION
+            ),
+            LoadError::MissingSystem(name) => write!(f, "missing registered system: {}", name),
+            LoadError::UnexpectedSystem(name) => {
+                write!(f, "system was not registered when saved: {}", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Format(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<bincode::Error> for LoadError {
+    fn from(error: bincode::Error) -> Self {
+        LoadError::Format(error)
+    }
+}
+
+impl SystemState {
+    /// Writes the state, the system schedules and the journal. Registered systems are saved by name only.
+    pub fn save<W: Write>(&self, mut writer: W) -> Result<(), bincode::Error> {
+        bincode::serialize_into(&mut writer, &SAVE_VERSION)?;
+        bincode::serialize_into(&mut writer, &self.state)?;
+        bincode::serialize_into(&mut writer, &self.systems.to_save())?;
+        bincode::serialize_into(&mut writer, &self.journal)
+    }
+
+    /// Reads a saved state, `systems` must contain each system that was registered when saved
+    pub fn load<R: Read>(
+        mut reader: R,
+        systems: Vec<Box<dyn CustomSystem>>,
+    ) -> Result<Self, LoadError> {
+        let version: u32 = bincode::deserialize_from(&mut reader)?;
+
+        if version != SAVE_VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+
+        let mut state: State = bincode::deserialize_from(&mut reader)?;
+        state.rebuild_indices();
+        let systems_save: SystemQueueSave = bincode::deserialize_from(&mut reader)?;
+        let systems = SystemQueue::from_save(systems_save, systems)?;
+        let journal: Journal = bincode::deserialize_from(&mut reader)?;
+
+        Ok(Self {
+            state,
+            systems,
+            journal,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::*;
+    use crate::system_state::examples::{save, shipping_route};
+    use crate::systems::{CustomSystem, System};
+
+    #[derive(Debug, Clone)]
+    struct Tax;
+
+    impl CustomSystem for Tax {
+        fn name(&self) -> &str {
+            "Tax"
+        }
+
+        fn get_interval(&self) -> Duration {
+            30.0 * DAY
+        }
+
+        fn run(&mut self, _state: &mut State) {}
+    }
+
+    #[test]
+    fn loaded_state_continues_identically() {
+        let mut state = shipping_route();
+        state.register(Tax).unwrap();
+        state
+            .set_interval(System::ResourceDecay, 10.0 * DAY)
+            .unwrap();
+        state.update_by(45.0 * DAY).unwrap();
+
+        let mut loaded = SystemState::load(save(&state).as_slice(), vec![Box::new(Tax)]).unwrap();
+        assert_eq!(save(&state), save(&loaded));
+
+        state.update_by(90.0 * DAY).unwrap();
+        loaded.update_by(90.0 * DAY).unwrap();
+
+        assert_eq!(save(&state), save(&loaded));
+    }
+
+    #[test]
+    fn loaded_state_keeps_pending_updates() {
+        let mut state = shipping_route();
+        state.register(Tax).unwrap();
+        state.update_by(2.0 * DAY).unwrap();
+        state.pause(System::ColonyPopulation);
+        state
+            .set_interval(System::ShippingAverage, 3.0 * DAY)
+            .unwrap();
+
+        let loaded = SystemState::load(save(&state).as_slice(), vec![Box::new(Tax)]).unwrap();
+
+        assert_eq!(
+            state.systems.get_next_updates(),
+            loaded.systems.get_next_updates()
+        );
+        assert_eq!(
+            state.systems.get_pending_updates(),
+            loaded.systems.get_pending_updates()
+        );
+    }
+
+    #[test]
+    fn loaded_state_rebuilds_lookups() {
+        let state = shipping_route();
+        let loaded = SystemState::load(save(&state).as_slice(), vec![]).unwrap();
+
+        let sol = state.state.star.get_by_name("Sol");
+        assert!(sol.is_some());
+        assert_eq!(sol, loaded.state.star.get_by_name("Sol"));
+        assert_eq!(
+            sol,
+            loaded.state.star.get_nearest(GalaxyPosition::default())
+        );
+        assert_eq!(
+            state.state.body.get_by_name("Luna"),
+            loaded.state.body.get_by_name("Luna")
+        );
+    }
+
+    #[test]
+    fn load_requires_registered_systems() {
+        let mut state = shipping_route();
+        state.register(Tax).unwrap();
+        let bytes = save(&state);
+
+        let result = SystemState::load(bytes.as_slice(), vec![]);
+
+        assert!(matches!(result, Err(LoadError::MissingSystem(name)) if name == "Tax"));
+    }
+
+    #[test]
+    fn load_rejects_other_versions() {
+        let mut bytes = save(&shipping_route());
+        bytes[..4].copy_from_slice(&(SAVE_VERSION + 1).to_le_bytes());
+
+        let result = SystemState::load(bytes.as_slice(), vec![]);
+
+        assert!(matches!(result, Err(LoadError::UnsupportedVersion(_))));
+    }
+}
@@ -0,0 +1,598 @@
+use crate::body::ephemeris::Ephemeris;
+use crate::body::Planet;
+use crate::colony::economy::ProductionUnit;
+use crate::colony::{Colony, ColonyLinks};
+use crate::command::Command;
+use crate::components::*;
+use crate::ships::drives::Drive;
+use crate::ships::freighter_assignment::Assignment;
+use crate::ships::Freighter;
+use crate::star::examples::sol_system;
+use crate::star::galaxy::GalaxyGenerator;
+use crate::star::{Star, StarSystem};
+use crate::system_state::SystemState;
+use crate::time::{checked_add, DateTime, TimeError};
+use crate::HashMap;
+use gen_id::Id;
+use iter_context::ContextualIterator;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+
+/// A world and a run configuration, loaded from a RON file by the `scenario` binary
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    pub start: DateTime,
+    pub end: DateTime,
+    /// Days between reports, which must be positive
+    pub report_interval: f64,
+    pub star_systems: Vec<StarSystemConfig>,
+    pub colonies: Vec<ColonyConfig>,
+    #[serde(default)]
+    pub freighters: Vec<FreighterConfig>,
+    /// Checked at the start, at every report and at the end of the run
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+}
+
+/// A star system described in full, one bundled with the crate, or many generated at random
+#[derive(Debug, Clone, Deserialize)]
+pub enum StarSystemConfig {
+    /// e.g., `Bundled("Sol")`, with the planets where they are in the sky at the start date
+    Bundled(String),
+    /// Values are in SI units, e.g., kilograms, meters and radians per second
+    System { star: Star, planets: Vec<Planet> },
+    /// e.g., `Generated((seed: 7, star_count: 200))`
+    Generated(GalaxyGenerator),
+}
+
+impl StarSystemConfig {
+    fn resolve(&self, start: DateTime) -> Result<Vec<StarSystem>, ScenarioError> {
+        match self {
+            StarSystemConfig::Bundled(name) => match name.as_str() {
+                "Sol" => {
+                    let mut sol = sol_system();
+                    Ephemeris::bundled().apply(&mut sol, start);
+                    Ok(vec![sol])
+                }
+                _ => Err(ScenarioError::UnknownStarSystem(name.clone())),
+            },
+            StarSystemConfig::System { star, planets } => Ok(vec![StarSystem {
+                star: star.clone(),
+                planets: planets.clone(),
+            }]),
+            StarSystemConfig::Generated(generator) => Ok(generator.generate()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColonyConfig {
+    pub name: String,
+    pub body: String,
+    /// Millions of people
+    pub population: f64,
+    /// The capacity of each facility in tons per day
+    #[serde(default)]
+    pub production: Vec<(Facility, f64)>,
+    /// Multiplies the default price of each listed resource
+    #[serde(default)]
+    pub prices: Vec<(Resource, f64)>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FreighterConfig {
+    pub name: String,
+    #[serde(default = "one")]
+    pub count: u32,
+    /// Colony name
+    pub location: String,
+    /// Tons
+    pub tonnage: f64,
+    /// Tons
+    pub capacity: f64,
+    /// Tons per minute
+    pub loading_rate: f64,
+    /// Kilometers per second
+    pub speed: f64,
+    /// Colony names
+    pub route: Option<(String, String)>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum Assertion {
+    MinSatiation { colony: String, min: f64 },
+    MinPopulation { colony: String, millions: f64 },
+}
+
+impl Assertion {
+    fn get_colony(&self) -> &str {
+        match self {
+            Assertion::MinSatiation { colony, .. } | Assertion::MinPopulation { colony, .. } => {
+                colony
+            }
+        }
+    }
+}
+
+impl Display for Assertion {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Assertion::MinSatiation { colony, min } => {
+                write!(f, "{} satiation stays above {}", colony, min)
+            }
+            Assertion::MinPopulation { colony, millions } => {
+                write!(f, "{} population stays above {} M", colony, millions)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Parse(ron::Error),
+    Io(std::io::Error),
+    Time(TimeError),
+    UnknownStarSystem(String),
+    DuplicateBody(String),
+    DuplicateColony(String),
+    /// The run would never reach its end date
+    NonPositiveReportInterval(f64),
+    UnknownBody {
+        colony: String,
+        body: String,
+    },
+    /// A freighter or assertion refers to a colony that is not defined
+    UnknownColony {
+        referenced_by: String,
+        colony: String,
+    },
+}
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ScenarioError::Parse(error) => write!(f, "invalid scenario: {}", error),
+            ScenarioError::Io(error) => write!(f, "{}", error),
+            ScenarioError::Time(error) => write!(f, "{}", error),
+            ScenarioError::UnknownStarSystem(name) => {
+                write!(f, "unknown bundled star system '{}'", name)
+            }
+            ScenarioError::DuplicateBody(name) => {
+                write!(f, "more than one body is named '{}'", name)
+            }
+            ScenarioError::DuplicateColony(name) => {
+                write!(f, "more than one colony is named '{}'", name)
+            }
+            ScenarioError::NonPositiveReportInterval(days) => {
+                write!(f, "report interval of {} days is not positive", days)
+            }
+            ScenarioError::UnknownBody { colony, body } => {
+                write!(f, "colony '{}' refers to unknown body '{}'", colony, body)
+            }
+            ScenarioError::UnknownColony {
+                referenced_by,
+                colony,
+            } => write!(
+                f,
+                "'{}' refers to unknown colony '{}'",
+                referenced_by, colony
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<ron::Error> for ScenarioError {
+    fn from(error: ron::Error) -> Self {
+        ScenarioError::Parse(error)
+    }
+}
+
+impl From<std::io::Error> for ScenarioError {
+    fn from(error: std::io::Error) -> Self {
+        ScenarioError::Io(error)
+    }
+}
+
+impl From<TimeError> for ScenarioError {
+    fn from(error: TimeError) -> Self {
+        ScenarioError::Time(error)
+    }
+}
+
+/// An assertion that did not hold, with the first date at which it was found to fail
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub date: DateTime,
+    pub assertion: Assertion,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}: failed on {}", self.assertion, self.date)
+    }
+}
+
+/// A scenario that has been built into a `SystemState`
+#[derive(Debug)]
+pub struct World {
+    pub state: SystemState,
+    colonies: HashMap<String, Id<Colony>>,
+}
+
+impl World {
+    pub fn get_colony(&self, name: &str) -> Option<Id<Colony>> {
+        self.colonies.get(name).copied()
+    }
+
+    fn resolve_colony(&self, name: &str, referenced_by: &str) -> Result<Id<Colony>, ScenarioError> {
+        self.get_colony(name)
+            .ok_or_else(|| ScenarioError::UnknownColony {
+                referenced_by: referenced_by.to_string(),
+                colony: name.to_string(),
+            })
+    }
+
+    /// Returns true if the assertion holds
+    pub fn check(&self, assertion: &Assertion) -> Result<bool, ScenarioError> {
+        let colonies = &self.state.state.colony;
+        let colony = self.resolve_colony(assertion.get_colony(), &assertion.to_string())?;
+
+        match assertion {
+            Assertion::MinSatiation { min, .. } => {
+                Ok(colonies.people.satiation.get(colony).value() > *min)
+            }
+            Assertion::MinPopulation { millions, .. } => {
+                Ok(*colonies.people.population.get(colony) > Population::in_millions(*millions))
+            }
+        }
+    }
+
+    pub fn report<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let colonies = &self.state.state.colony;
+        writeln!(writer, "{}", self.state.state.time)?;
+
+        let mut names = self.colonies.iter().collect::<Vec<_>>();
+        names.sort_by_key(|(name, _)| *name);
+
+        for (name, id) in names {
+            let id = *id;
+            writeln!(
+                writer,
+                "  {:<24} population: {:<10} satiation: {:.3}  food: {}",
+                name,
+                colonies.people.population.get(id).to_string(),
+                colonies.people.satiation.get(id).value(),
+                colonies
+                    .resources
+                    .stockpile
+                    .get(Resource::Food)
+                    .get(id)
+                    .tons(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Scenario {
+    pub fn from_ron(text: &str) -> Result<Self, ScenarioError> {
+        Ok(ron::from_str(text)?)
+    }
+
+    /// Creates the star systems, colonies and freighters, resolving references by name
+    pub fn build(&self) -> Result<World, ScenarioError> {
+        // negated so that NaN is rejected too
+        if !(self.report_interval > 0.0) {
+            return Err(ScenarioError::NonPositiveReportInterval(
+                self.report_interval,
+            ));
+        }
+
+        let mut state = SystemState::new(self.start);
+
+        for config in &self.star_systems {
+            for star_system in config.resolve(self.start)? {
+                state.state.create_star_system(star_system);
+            }
+        }
+
+        let mut body_names = crate::HashSet::default();
+        for name in state.state.body.name.iter().into_iter() {
+            if !body_names.insert(name) {
+                return Err(ScenarioError::DuplicateBody(name.clone()));
+            }
+        }
+
+        let mut world = World {
+            state,
+            colonies: HashMap::default(),
+        };
+
+        for config in &self.colonies {
+            world.create_colony(config)?;
+        }
+
+        for config in &self.freighters {
+            world.create_freighters(config)?;
+        }
+
+        for assertion in &self.assertions {
+            world.resolve_colony(assertion.get_colony(), &assertion.to_string())?;
+        }
+
+        Ok(world)
+    }
+
+    /// Runs the scenario to its end date, writing a report at each interval
+    pub fn run<W: Write>(&self, writer: &mut W) -> Result<Vec<Failure>, ScenarioError> {
+        let mut world = self.build()?;
+        let mut failures = Vec::<Failure>::new();
+        let mut failed = vec![false; self.assertions.len()];
+        let interval = Duration::in_days(self.report_interval);
+
+        loop {
+            let now = world.state.state.time.get_date_time();
+            world.report(writer)?;
+
+            for (assertion, failed) in self.assertions.iter().zip(failed.iter_mut()) {
+                if !*failed && !world.check(assertion)? {
+                    *failed = true;
+                    failures.push(Failure {
+                        date: now,
+                        assertion: assertion.clone(),
+                    });
+                }
+            }
+
+            if now >= self.end {
+                break;
+            }
+
+            let next = checked_add(now, interval)?.min(self.end);
+            world.state.update(next)?;
+        }
+
+        Ok(failures)
+    }
+}
+
+impl World {
+    fn create_colony(&mut self, config: &ColonyConfig) -> Result<(), ScenarioError> {
+        if self.colonies.contains_key(&config.name) {
+            return Err(ScenarioError::DuplicateColony(config.name.clone()));
+        }
+
+        let bodies = &self.state.state.body;
+        let body = bodies
+            .get_by_name(&config.body)
+            .ok_or_else(|| ScenarioError::UnknownBody {
+                colony: config.name.clone(),
+                body: config.body.clone(),
+            })?;
+        let habitability = bodies.get_habitability(body, &self.state.state.star);
+
+        let colony = Colony {
+            name: config.name.clone(),
+            population: Population::in_millions(config.population),
+        };
+        let id = self.state.create_colony(colony, ColonyLinks { body });
+        self.colonies.insert(config.name.clone(), id);
+
+        for (facility, capacity) in &config.production {
+            let capacity = MassRate::in_tons_per_day(*capacity);
+            let unit = ProductionUnit::new(capacity, facility.get_output(), habitability);
+
+            self.state.apply(Command::InsertProduction {
+                colony: id,
+                facility: *facility,
+                unit,
+            });
+        }
+
+        for (resource, multiplier) in &config.prices {
+            self.state.apply(Command::SetPrice {
+                colony: id,
+                resource: *resource,
+                price: resource.get_default_price() * *multiplier,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn create_freighters(&mut self, config: &FreighterConfig) -> Result<(), ScenarioError> {
+        let location = self.resolve_colony(&config.location, &config.name)?;
+
+        let route = match &config.route {
+            Some((a, b)) => {
+                let a = self.resolve_colony(a, &config.name)?;
+                let b = self.resolve_colony(b, &config.name)?;
+                Some(Assignment::Route(a, b))
+            }
+            None => None,
+        };
+
+        for i in 0..config.count {
+            let name = if config.count == 1 {
+                config.name.clone()
+            } else {
+                format!("{} {}", config.name, i + 1)
+            };
+
+            let freighter = Freighter {
+                name,
+                tonnage: config.tonnage * TON,
+                capacity: config.capacity * TON,
+                loading_rate: config.loading_rate * TON / MIN,
+                shipping_cost: PricePerMeter::in_credits_per_kg_m(1.0 / 150e9),
+                drive: Drive::Warp(config.speed * KM / S),
+            };
+
+            let freighter = self.state.create_freighter(freighter, location);
+
+            if route.is_some() {
+                self.state.apply(Command::AssignFreighter {
+                    freighter,
+                    assignment: route,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"(
+        start: "2050-01-01T00:00:00",
+        end: "2050-01-21T00:00:00",
+        report_interval: 10.0,
+        star_systems: [Bundled("Sol")],
+        colonies: [
+            (
+                name: "Farmland Earth",
+                body: "Earth",
+                population: 100.0,
+                production: [(Farmland, 250.0)],
+            ),
+            (
+                name: "Lunar City",
+                body: "Luna",
+                population: 10.0,
+                prices: [(Food, 4.0)],
+            ),
+        ],
+        freighters: [
+            (
+                name: "Freighter",
+                count: 2,
+                location: "Farmland Earth",
+                tonnage: 500.0,
+                capacity: 1250.0,
+                loading_rate: 2.0,
+                speed: 50.0,
+                route: Some(("Farmland Earth", "Lunar City")),
+            ),
+        ],
+        assertions: [
+            MinPopulation(colony: "Lunar City", millions: 9.0),
+            MinPopulation(colony: "Lunar City", millions: 11.0),
+        ],
+    )"#;
+
+    #[test]
+    fn scenario_reports_failed_assertions() {
+        let scenario = Scenario::from_ron(SCENARIO).unwrap();
+        let mut report = Vec::new();
+
+        let failures = scenario.run(&mut report).unwrap();
+
+        assert_eq!(1, failures.len());
+        assert_eq!(scenario.start, failures[0].date);
+
+        let report = String::from_utf8(report).unwrap();
+        assert_eq!(3, report.matches("Lunar City").count());
+    }
+
+    #[test]
+    fn unknown_colony_is_an_error() {
+        let scenario = SCENARIO.replace("\"Lunar City\")", "\"Lunar Town\")");
+        let scenario = Scenario::from_ron(&scenario).unwrap();
+
+        let error = scenario.build().unwrap_err();
+
+        assert_eq!(
+            "'Freighter' refers to unknown colony 'Lunar Town'",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn unknown_body_is_an_error() {
+        let scenario = SCENARIO.replace("body: \"Luna\"", "body: \"Lunar\"");
+        let scenario = Scenario::from_ron(&scenario).unwrap();
+
+        let error = scenario.build().unwrap_err();
+
+        assert_eq!(
+            "colony 'Lunar City' refers to unknown body 'Lunar'",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn non_positive_report_interval_is_an_error() {
+        let scenario = SCENARIO.replace("report_interval: 10.0", "report_interval: 0.0");
+        let scenario = Scenario::from_ron(&scenario).unwrap();
+
+        let error = scenario.run(&mut Vec::new()).unwrap_err();
+
+        assert_eq!(
+            "report interval of 0 days is not positive",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn star_systems_can_be_described_in_full() {
+        let system = r#"System(
+            star: (name: "Proxima Centauri", position: (x: 4.0e16, y: 0.0), star_type: M(0.45)),
+            planets: [
+                (
+                    body: (
+                        name: "Proxima b",
+                        mass: 7.6e24,
+                        radius: 7.2e6,
+                        orbit: (radius: 7.3e9, angular_speed: 5.66e-6, offset: 0.0),
+                        properties: (
+                            surface: Continental(land: 0.5),
+                            pressure: Ideal,
+                            oxygen: Ideal,
+                            hydrosphere: Dynamic,
+                            biosphere: Primordial,
+                            magnetosphere: Absent,
+                        ),
+                    ),
+                    moons: [],
+                ),
+            ],
+        )"#;
+        let scenario = SCENARIO.replace("Bundled(\"Sol\")", system);
+        let scenario = scenario.replace("body: \"Earth\"", "body: \"Proxima b\"");
+        let scenario = scenario.replace("body: \"Luna\"", "body: \"Proxima b\"");
+        let scenario = Scenario::from_ron(&scenario).unwrap();
+
+        let world = scenario.build().unwrap();
+
+        let proxima_b = world.state.state.body.get_by_name("Proxima b").unwrap();
+        let lunar_city = world.get_colony("Lunar City").unwrap();
+        assert_eq!(proxima_b, world.state.state.colony.get_body(lunar_city));
+    }
+
+    #[test]
+    fn generated_star_systems_are_added_alongside_others() {
+        let systems = r#"Bundled("Sol"), Generated((seed: 7, star_count: 50))"#;
+        let scenario = SCENARIO.replace("Bundled(\"Sol\")", systems);
+        let scenario = Scenario::from_ron(&scenario).unwrap();
+
+        let world = scenario.build().unwrap();
+
+        assert_eq!(51, world.state.state.star.alloc.ids().into_iter().count());
+    }
+
+    #[test]
+    fn bundled_scenarios_parse() {
+        Scenario::from_ron(include_str!("../scenarios/lunar_city.ron")).unwrap();
+    }
+}
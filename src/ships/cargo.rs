@@ -1,6 +1,7 @@
 use crate::components::{Mass, Resource};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CargoEntry {
     pub resource: Resource,
     pub amount: Mass,
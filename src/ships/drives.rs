@@ -1,6 +1,7 @@
 use super::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum Drive {
     Warp(Speed),
 }
@@ -22,10 +23,7 @@ impl Drive {
         let to_star = bodies.star.get(to_body);
 
         if from_star == to_star {
-            let from_orbit = bodies.orbit.get(from_body);
-            let to_orbit = bodies.orbit.get(to_body);
-
-            self.calculate_trip_duration_in_system(from_orbit, to_orbit, departure)
+            self.calculate_trip_duration_in_system(*from_body, *to_body, departure, bodies)
         } else {
             let from_position = stars.position.get(from_star);
             let to_position = stars.position.get(to_star);
@@ -36,16 +34,17 @@ impl Drive {
 
     fn calculate_trip_duration_in_system(
         &self,
-        from_orbit: &BodyOrbit,
-        to_orbit: &BodyOrbit,
+        from_body: Id<Body>,
+        to_body: Id<Body>,
         departure: TimeFloat,
+        bodies: &Bodies,
     ) -> Duration {
-        let from_position = from_orbit.calculate_position(departure);
+        let from_position = bodies.get_position(from_body, departure);
         let mut duration = Duration::zero();
 
         // Newton's method used to compensate for orbital motion affecting trip length
         for _ in 0..5 {
-            let to_position = to_orbit.calculate_position(departure + duration);
+            let to_position = bodies.get_position(to_body, departure + duration);
 
             let distance = (from_position - to_position).magnitude();
 
@@ -57,7 +56,11 @@ impl Drive {
         duration
     }
 
-    fn calculate_trip_duration_interstellar(&self, from: Position, to: Position) -> Duration {
+    fn calculate_trip_duration_interstellar(
+        &self,
+        from: GalaxyPosition,
+        to: GalaxyPosition,
+    ) -> Duration {
         let distance = (from - to).magnitude();
 
         match self {
@@ -65,3 +68,19 @@ impl Drive {
         }
     }
 }
+
+#[cfg(all(test, feature = "three_dimensional"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interstellar_trips_include_every_axis() {
+        let drive = Drive::Warp(1.0 * KM / S);
+        let from = GalaxyPosition::in_m_3d(0.0, 0.0, 0.0);
+        let to = GalaxyPosition::in_m_3d(2e3, 3e3, 6e3);
+
+        let duration = drive.calculate_trip_duration_interstellar(from, to);
+
+        assert_eq!(Duration::in_s(7.0), duration);
+    }
+}
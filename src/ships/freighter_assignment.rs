@@ -1,7 +1,8 @@
 use crate::colony::Colony;
 use gen_id::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum Assignment {
     Route(Id<Colony>, Id<Colony>),
 }
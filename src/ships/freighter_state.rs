@@ -8,12 +8,10 @@ use crate::ships::cargo::CargoEntry;
 use crate::ships::drives::Drive;
 use crate::ships::freighter_assignment::Assignment;
 use crate::star::Stars;
-use crate::systems::System;
 use crate::time::TimeState;
 use gen_id::*;
 use iter_context::ContextualIterator;
-
-const INTERVAL: Duration = System::FreighterState.get_interval();
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 table_array! {
     struct FreighterState {
@@ -31,7 +29,7 @@ table_array! {
             loading: struct Loading {
                 type Row = struct LoadingRow;
                 fields {
-                    abort: bool,
+                    completion: TimeFloat,
                 }
                 links {
                     location: Colony,
@@ -78,6 +76,7 @@ pub struct Parameters<'a> {
     pub loading_rate: &'a Component<Freighter, MassRate>,
     pub shipping_cost: &'a Component<Freighter, PricePerMeter>,
     pub drive: &'a Component<Freighter, Drive>,
+    pub events: &'a mut IndexedMinQueue<Freighter, TimeFloat>,
 
     pub time: &'a TimeState,
     pub stars: &'a Stars,
@@ -86,7 +85,8 @@ pub struct Parameters<'a> {
 }
 
 impl<'a> Parameters<'a> {
-    fn get_unloading_duration<I>(&self, id: I) -> Duration
+    /// The time taken to move the contents of the hold at the freighter's loading rate
+    fn get_transfer_duration<I>(&self, id: I) -> Duration
     where
         I: ValidId<Freighter>,
     {
@@ -96,25 +96,20 @@ impl<'a> Parameters<'a> {
         contents / loading_rate
     }
 
-    fn get_trip_duration<F, C>(&self, id: F, from: C, to: C) -> Duration
+    fn get_trip_duration<F, C>(&self, id: F, from: C, to: C, departure: TimeFloat) -> Duration
     where
         F: ValidId<Freighter>,
         C: ValidId<Colony>,
     {
         let drive = self.drive.get(id);
-        let time = self.time.get_time();
 
-        drive.calculate_trip_duration(from, to, time, self.colonies, self.bodies, self.stars)
+        drive.calculate_trip_duration(from, to, departure, self.colonies, self.bodies, self.stars)
     }
 
     fn contents<F: ValidId<Freighter>>(&self, id: F) -> Mass {
         self.cargo.get(id).iter().map(|c| c.amount).sum::<Mass>()
     }
 
-    fn is_cargo_full<F: ValidId<Freighter>>(&self, id: F) -> bool {
-        self.contents(id) >= *self.capacity.get(id)
-    }
-
     fn get_price_gradient<I: ValidId<Colony>>(
         &self,
         source: I,
@@ -146,6 +141,79 @@ impl<'a> Parameters<'a> {
             .get_mut(colony)
     }
 
+    fn schedule<F>(&mut self, id: F, time: TimeFloat)
+    where
+        F: ValidId<Freighter>,
+    {
+        self.events.insert(id, time);
+    }
+
+    fn pop_due_events(&mut self, until: TimeFloat) -> Vec<Id<Freighter>> {
+        let mut due = Vec::new();
+
+        while self.events.peek().map_or(false, |time| *time <= until) {
+            if let Some((id, _)) = self.events.pop() {
+                due.push(id);
+            }
+        }
+
+        due
+    }
+
+    /// Fills the hold with the most profitable resources in stock, returning the time at which
+    /// the cargo is aboard. The freighter departs empty if nothing is worth shipping.
+    fn load<F>(&mut self, id: F, location: Id<Colony>, destination: Id<Colony>) -> TimeFloat
+    where
+        F: ValidId<Freighter> + Copy,
+    {
+        let shipping_cost = self.shipping_cost.get(id);
+        let from_body = self.colonies.body[location];
+        let to_body = self.colonies.body[destination];
+        let distance =
+            self.bodies
+                .get_distance(from_body, to_body, self.time.get_time(), self.stars);
+        let cost = shipping_cost * distance.magnitude();
+
+        let mut price_difference = ResourceArray::<Price>::default();
+        price_difference
+            .iter_enum_mut()
+            .for_each(|(price, resource)| {
+                *price = self.get_price_gradient(location, destination, cost, *resource);
+            });
+
+        let mut to_load = *self.capacity.get(id) - self.contents(id);
+
+        while to_load.is_some() {
+            let resource = price_difference
+                .iter_enum()
+                .into_iter()
+                .filter(|(price, resource)| {
+                    **price > Price::zero() && self.get_stockpile(location, **resource).is_some()
+                })
+                .reduce(|(max_price, max_resource), (price, resource)| {
+                    if price > max_price {
+                        (price, resource)
+                    } else {
+                        (max_price, max_resource)
+                    }
+                })
+                .map(|(_, resource)| *resource);
+
+            match resource {
+                Some(resource) => {
+                    let loaded = self.get_stockpile_mut(location, resource).request(to_load);
+
+                    to_load -= loaded;
+
+                    self.add_cargo(id, resource, loaded);
+                }
+                None => break,
+            }
+        }
+
+        self.time.get_time() + self.get_transfer_duration(id)
+    }
+
     fn add_cargo<F>(&mut self, id: F, resource: Resource, amount: Mass)
     where
         F: ValidId<Freighter>,
@@ -161,10 +229,27 @@ impl<'a> Parameters<'a> {
 }
 
 impl FreighterState {
+    /// Sends idle freighters on their assignments
     pub fn update(&mut self, parameters: &mut Parameters) {
         let idle = &mut self.idle;
         let assign = &mut self.assign;
 
+        let moving = &mut self.moving;
+
+        let loading = &mut self.loading;
+
+        let indices = &mut self.indices;
+
+        // loading, arrivals and unloading are scheduled as events, see `update_events`
+        assign.transition(idle, moving, loading, indices, parameters);
+    }
+
+    /// Transitions the freighters whose loading, arrival or unloading completes at or before the given time,
+    /// returning the number of events handled
+    pub fn update_events(&mut self, until: TimeFloat, parameters: &mut Parameters) -> usize {
+        let idle = &mut self.idle;
+        let assign = &mut self.assign;
+
         let moving = &mut self.moving;
         let arrivals = &mut self.arrivals;
 
@@ -176,15 +261,27 @@ impl FreighterState {
 
         let indices = &mut self.indices;
 
-        // do updates before transitions
-        // otherwise, a new arrival would spend the hour unloading
-        loading.update(parameters);
+        let mut count = 0;
+
+        // an arrival with an empty hold schedules its unloading for the same instant
+        loop {
+            let due = parameters.pop_due_events(until);
+
+            if due.is_empty() {
+                break;
+            }
+
+            count += due.len();
+
+            // each state before those that lead to it, so that a freighter moves once per batch
+            unloaded.transition(&due, unloading, idle, indices, parameters);
+            arrivals.transition(&due, moving, unloading, indices, parameters);
+            loaded.transition(&due, loading, moving, unloading, indices, parameters);
+        }
 
-        // each transition can lead into the next if applicable
-        arrivals.transition(moving, unloading, indices, parameters);
-        unloaded.transition(unloading, idle, indices, parameters);
         assign.transition(idle, moving, loading, indices, parameters);
-        loaded.transition(loading, moving, unloading, indices, parameters);
+
+        count
     }
 }
 
@@ -243,29 +340,35 @@ impl Assign {
             );
 
             match assignment {
+                Assignment::Route(a, b) if a.eq(&b) => {
+                    // a route to the same colony would never take any time
+                    parameters.assignment.insert(id, None);
+                    idle.insert(id, Valid::assert(idle_row), indices);
+                }
                 Assignment::Route(a, b) => {
                     if idle_row.location.eq(&a) {
-                        let row = LoadingRow::new(false, idle_row.location, b);
+                        let completion = parameters.load(id, idle_row.location, b);
+                        let row = LoadingRow::new(completion, idle_row.location, b);
                         loading.insert(id, row, indices);
+                        parameters.schedule(id, completion);
                     } else if idle_row.location.eq(&b) {
-                        let row = LoadingRow::new(false, idle_row.location, a);
+                        let completion = parameters.load(id, idle_row.location, a);
+                        let row = LoadingRow::new(completion, idle_row.location, a);
                         loading.insert(id, row, indices);
+                        parameters.schedule(id, completion);
                     } else {
                         // go to nearest
-                        let to_a = parameters.get_trip_duration(id, idle_row.location, a);
-                        let to_b = parameters.get_trip_duration(id, idle_row.location, b);
+                        let to_a = parameters.get_trip_duration(id, idle_row.location, a, time);
+                        let to_b = parameters.get_trip_duration(id, idle_row.location, b, time);
 
                         let (destination, duration) =
                             if to_a > to_b { (b, to_b) } else { (a, to_a) };
 
                         if duration <= Duration::MAX {
-                            let row = MovingRow::new(
-                                time,
-                                time + duration,
-                                idle_row.location,
-                                destination,
-                            );
+                            let arrival = time + duration;
+                            let row = MovingRow::new(time, arrival, idle_row.location, destination);
                             moving.insert(id, row, indices);
+                            parameters.schedule(id, arrival);
                         } else {
                             // destination unreachable
                             parameters.assignment.insert(id, None);
@@ -286,23 +389,23 @@ pub struct Arrivals {
 impl Arrivals {
     pub fn transition(
         &mut self,
+        due: &[Id<Freighter>],
         moving: &mut Moving,
         unloading: &mut Unloading,
         indices: &mut Indices,
-        parameters: &Parameters,
+        parameters: &mut Parameters,
     ) {
-        self.get_arrivals(&moving.arrival, parameters);
+        self.get_arrivals(due, indices);
         self.transition_arrivals(moving, unloading, indices, parameters);
     }
 
-    fn get_arrivals(&mut self, arrival: &Column<Moving, TimeFloat>, parameters: &Parameters) {
-        let time = parameters.time.get_time();
-
-        let iter = arrival
+    fn get_arrivals(&mut self, due: &[Id<Freighter>], indices: &Indices) {
+        let iter = due
             .iter()
-            .zip(arrival.indices())
-            .into_iter()
-            .filter_map(|(arrival, id)| (*arrival < time).then_some(id));
+            .filter_map(|id| match indices.get(Valid::assert(*id)) {
+                FreighterStateIndex::Moving(index) => Some(index.clone()),
+                _ => None,
+            });
 
         self.transition.fill(iter);
     }
@@ -312,7 +415,7 @@ impl Arrivals {
         moving: &mut Moving,
         unloading: &mut Unloading,
         indices: &mut Indices,
-        parameters: &Parameters,
+        parameters: &mut Parameters,
     ) {
         self.transition.drain().for_each(|index| {
             // TODO reconfigure to use Valid<Moving>::swap_remove(index, indices) -> Valid<MovingRow>
@@ -330,6 +433,7 @@ impl Arrivals {
             let row = UnloadingRow::new(row.arrival, complete, row.destination);
 
             unloading.insert(id, row, indices);
+            parameters.schedule(id, complete);
         });
     }
 }
@@ -342,24 +446,23 @@ pub struct Unloaded {
 impl Unloaded {
     pub fn transition(
         &mut self,
+        due: &[Id<Freighter>],
         unloading: &mut Unloading,
         idling: &mut Idle,
         indices: &mut Indices,
         parameters: &mut Parameters,
     ) {
-        self.get_unloaded(unloading, parameters);
+        self.get_unloaded(due, indices);
         self.transition_unloaded(unloading, idling, indices, parameters);
     }
 
-    fn get_unloaded(&mut self, unloading: &Unloading, parameters: &Parameters) {
-        let time = parameters.time.get_time();
-
-        let iter = unloading
-            .completion
+    fn get_unloaded(&mut self, due: &[Id<Freighter>], indices: &Indices) {
+        let iter = due
             .iter()
-            .zip(unloading.indices())
-            .into_iter()
-            .filter_map(|(completion, index)| (*completion < time).then_some(index));
+            .filter_map(|id| match indices.get(Valid::assert(*id)) {
+                FreighterStateIndex::Unloading(index) => Some(index.clone()),
+                _ => None,
+            });
 
         self.transition.fill(iter);
     }
@@ -393,71 +496,6 @@ impl Unloaded {
     }
 }
 
-impl Loading {
-    pub fn update(&mut self, parameters: &mut Parameters) {
-        let ids = self.id.iter().map(Valid::assert);
-        let mut price_difference = ResourceArray::<Price>::default();
-
-        ids.zip(self.location.iter())
-            .zip(self.destination.iter())
-            .zip(self.abort.iter_mut())
-            .for_each(|(((id, location), destination), abort)| {
-                let loading_rate = parameters.loading_rate.get(id);
-
-                let shipping_cost = parameters.shipping_cost.get(id);
-                let from_body = parameters.colonies.body[location];
-                let to_body = parameters.colonies.body[destination];
-                let distance = parameters.bodies.get_distance(
-                    from_body,
-                    to_body,
-                    parameters.time.get_time(),
-                    parameters.stars,
-                );
-                let cost = shipping_cost * distance.magnitude();
-
-                let capacity = parameters.capacity.get(id);
-                let contents = parameters.contents(id);
-
-                let remaining = capacity - contents;
-                let mut to_load = remaining.min(loading_rate * INTERVAL);
-
-                price_difference
-                    .iter_enum_mut()
-                    .for_each(|(price, resource)| {
-                        *price =
-                            parameters.get_price_gradient(location, destination, cost, *resource);
-                    });
-
-                while to_load.is_some() && !*abort {
-                    if let Some((_, resource)) = price_difference
-                        .iter_enum()
-                        .into_iter()
-                        .filter(|(price, resource)| {
-                            **price > Price::zero()
-                                && parameters.get_stockpile(location, **resource).is_some()
-                        })
-                        .reduce(|(max_price, max_resource), (price, resource)| {
-                            if price > max_price {
-                                (price, resource)
-                            } else {
-                                (max_price, max_resource)
-                            }
-                        })
-                    {
-                        let stock = parameters.get_stockpile_mut(location, *resource);
-                        let loaded = stock.request(to_load);
-
-                        to_load -= loaded;
-
-                        parameters.add_cargo(id, *resource, loaded);
-                    } else {
-                        *abort = true;
-                    }
-                }
-            });
-    }
-}
-
 #[derive(Debug, Default, Clone)]
 pub struct Loaded {
     transition: Transition<Index<Loading>>,
@@ -466,25 +504,23 @@ pub struct Loaded {
 impl Loaded {
     pub fn transition(
         &mut self,
+        due: &[Id<Freighter>],
         loading: &mut Loading,
         moving: &mut Moving,
         unloading: &mut Unloading,
         indices: &mut Indices,
         parameters: &mut Parameters,
     ) {
-        self.get_loaded(loading, parameters);
+        self.get_loaded(due, indices);
         self.transition_loaded(loading, moving, unloading, indices, parameters);
     }
 
-    fn get_loaded(&mut self, loading: &Loading, parameters: &Parameters) {
-        let ids = loading.id.iter().map(Valid::assert);
-
-        let iter = ids
-            .zip(loading.indices())
-            .zip(loading.abort.iter())
-            .into_iter()
-            .filter_map(|((id, index), abort)| {
-                (parameters.is_cargo_full(id) || *abort).then_some(index)
+    fn get_loaded(&mut self, due: &[Id<Freighter>], indices: &Indices) {
+        let iter = due
+            .iter()
+            .filter_map(|id| match indices.get(Valid::assert(*id)) {
+                FreighterStateIndex::Loading(index) => Some(index.clone()),
+                _ => None,
             });
 
         self.transition.fill(iter);
@@ -498,11 +534,10 @@ impl Loaded {
         indices: &mut Indices,
         parameters: &mut Parameters,
     ) {
-        let time = parameters.time.get_time();
-
         self.transition.drain().for_each(|index| {
             let (id, row) = loading.swap_remove(index, indices);
             let location = row.location;
+            let time = row.completion;
             let id = Valid::assert(id);
 
             let cargo_entries = parameters.cargo.get(id);
@@ -513,21 +548,38 @@ impl Loaded {
                 *shipped -= cargo.amount;
             }
 
-            match parameters.assignment.get(id) {
-                Some(Assignment::Route(a, destination)) if location.eq(a) => {
-                    let duration = parameters.get_trip_duration(id, location, *destination);
-                    let row = MovingRow::new(time, time + duration, location, destination);
+            let destination = match parameters.assignment.get(id) {
+                Some(Assignment::Route(a, destination)) if location.eq(a) => Some(*destination),
+                Some(Assignment::Route(destination, b)) if location.eq(b) => Some(*destination),
+                Some(Assignment::Route(_, _)) | None => None,
+            };
+
+            let trip = destination.map(|destination| {
+                let duration = parameters.get_trip_duration(id, location, destination, time);
+                (destination, duration)
+            });
+
+            match trip {
+                Some((destination, duration)) if duration <= Duration::MAX => {
+                    let arrival = time + duration;
+                    let row = MovingRow::new(time, arrival, location, destination);
                     moving.insert(id, row, indices);
+                    parameters.schedule(id, arrival);
                 }
-                Some(Assignment::Route(destination, b)) if location.eq(b) => {
-                    let duration = parameters.get_trip_duration(id, location, *destination);
-                    let row = MovingRow::new(time, time + duration, location, destination);
-                    moving.insert(id, row, indices);
+                Some(_) => {
+                    // destination unreachable, so the cargo is returned
+                    parameters.assignment.insert(id, None);
+
+                    let completion = time + parameters.get_transfer_duration(id);
+                    let row = UnloadingRow::new(time, completion, location);
+                    unloading.insert(id, row, indices);
+                    parameters.schedule(id, completion);
                 }
-                Some(Assignment::Route(_, _)) | None => {
-                    let duration = parameters.get_unloading_duration(id);
-                    let row = UnloadingRow::new(time, time + duration, row.destination);
+                None => {
+                    let completion = time + parameters.get_transfer_duration(id);
+                    let row = UnloadingRow::new(time, completion, row.destination);
                     unloading.insert(id, row, indices);
+                    parameters.schedule(id, completion);
                 }
             }
         });
@@ -546,4 +598,130 @@ impl Moving {
             None
         }
     }
+
+    /// The fraction of the trip completed at any time, clamped to the departure and arrival
+    pub fn get_trip_fraction_at(&self, index: &Index<Moving>, time: TimeFloat) -> Option<Fraction> {
+        let departure = *self.departure.get(index)?;
+        let arrival = *self.arrival.get(index)?;
+
+        if arrival > departure {
+            Some(Fraction::clamp((time - departure) / (arrival - departure)))
+        } else {
+            Some(Fraction::ONE)
+        }
+    }
+}
+
+/// The rows of each table in table order, so that reinserting them restores identical indices
+#[derive(Debug, Serialize, Deserialize)]
+struct FreighterStateRows {
+    idle: Vec<(Id<Freighter>, Id<Colony>)>,
+    loading: Vec<(Id<Freighter>, TimeFloat, Id<Colony>, Id<Colony>)>,
+    unloading: Vec<(Id<Freighter>, TimeFloat, TimeFloat, Id<Colony>)>,
+    moving: Vec<(Id<Freighter>, TimeFloat, TimeFloat, Id<Colony>, Id<Colony>)>,
+}
+
+impl From<&FreighterState> for FreighterStateRows {
+    fn from(state: &FreighterState) -> Self {
+        let idle = &state.idle;
+        let idle = idle
+            .id
+            .iter()
+            .zip(idle.location.iter())
+            .into_iter()
+            .map(|(id, location)| (*id, *location))
+            .collect();
+
+        let loading = &state.loading;
+        let loading = loading
+            .id
+            .iter()
+            .zip(loading.completion.iter())
+            .zip(loading.location.iter())
+            .zip(loading.destination.iter())
+            .into_iter()
+            .map(|(((id, completion), location), destination)| {
+                (*id, *completion, *location, *destination)
+            })
+            .collect();
+
+        let unloading = &state.unloading;
+        let unloading = unloading
+            .id
+            .iter()
+            .zip(unloading.arrival.iter())
+            .zip(unloading.completion.iter())
+            .zip(unloading.location.iter())
+            .into_iter()
+            .map(|(((id, arrival), completion), location)| (*id, *arrival, *completion, *location))
+            .collect();
+
+        let moving = &state.moving;
+        let moving = moving
+            .id
+            .iter()
+            .zip(moving.departure.iter())
+            .zip(moving.arrival.iter())
+            .zip(moving.source.iter())
+            .zip(moving.destination.iter())
+            .into_iter()
+            .map(|((((id, departure), arrival), source), destination)| {
+                (*id, *departure, *arrival, *source, *destination)
+            })
+            .collect();
+
+        Self {
+            idle,
+            loading,
+            unloading,
+            moving,
+        }
+    }
+}
+
+impl From<FreighterStateRows> for FreighterState {
+    fn from(rows: FreighterStateRows) -> Self {
+        let mut state = FreighterState::default();
+        let indices = &mut state.indices;
+
+        for (id, location) in rows.idle {
+            let row = IdleRow::new(location);
+            state.idle.insert(Valid::assert(id), row, indices);
+        }
+
+        for (id, completion, location, destination) in rows.loading {
+            let row = LoadingRow::new(completion, location, destination);
+            state.loading.insert(Valid::assert(id), row, indices);
+        }
+
+        for (id, arrival, completion, location) in rows.unloading {
+            let row = UnloadingRow::new(arrival, completion, location);
+            state.unloading.insert(Valid::assert(id), row, indices);
+        }
+
+        for (id, departure, arrival, source, destination) in rows.moving {
+            let row = MovingRow::new(departure, arrival, source, destination);
+            state.moving.insert(Valid::assert(id), row, indices);
+        }
+
+        state
+    }
+}
+
+impl Clone for FreighterState {
+    fn clone(&self) -> Self {
+        FreighterStateRows::from(self).into()
+    }
+}
+
+impl Serialize for FreighterState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        FreighterStateRows::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FreighterState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        FreighterStateRows::deserialize(deserializer).map(FreighterState::from)
+    }
 }
@@ -8,6 +8,7 @@ use crate::ships::cargo::CargoEntry;
 use drives::*;
 use freighter_assignment::*;
 use freighter_state::*;
+use serde::{Deserialize, Serialize};
 
 pub mod cargo;
 pub mod drives;
@@ -21,7 +22,7 @@ pub struct Shipping {
     pub freighters: Freighters,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Freighter {
     pub name: String,
     pub tonnage: Mass,
@@ -43,7 +44,7 @@ impl From<Id<Colony>> for FreighterLinks {
 
 dynamic_arena!(Freighter);
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Freighters {
     pub alloc: Allocator<Freighter>,
 
@@ -58,6 +59,9 @@ pub struct Freighters {
 
     pub assignment: Component<Freighter, Option<Assignment>>,
     pub state: FreighterState,
+
+    /// Completion times of loading and unloading freighters and arrival times of moving ones
+    pub events: IndexedMinQueue<Freighter, TimeFloat>,
 }
 
 impl Freighters {
@@ -100,6 +104,7 @@ impl Freighters {
 
             self.assignment.insert(id, None);
             self.state.remove(id);
+            self.events.remove(id);
 
             let id = id.id();
             self.alloc.kill(id);
@@ -120,6 +125,7 @@ impl Freighters {
             shipping_cost: &self.shipping_cost,
             capacity: &self.capacity,
             drive: &self.drive,
+            events: &mut self.events,
             time,
             colonies,
             bodies,
@@ -129,6 +135,35 @@ impl Freighters {
         self.state.update(parameters);
     }
 
+    pub fn update_events(
+        &mut self,
+        until: TimeFloat,
+        time: &TimeState,
+        colonies: &mut Colonies,
+        bodies: &Bodies,
+        stars: &Stars,
+    ) -> usize {
+        let parameters = &mut Parameters {
+            assignment: &mut self.assignment,
+            cargo: &mut self.cargo,
+            loading_rate: &self.loading_rate,
+            shipping_cost: &self.shipping_cost,
+            capacity: &self.capacity,
+            drive: &self.drive,
+            events: &mut self.events,
+            time,
+            colonies,
+            bodies,
+            stars,
+        };
+
+        self.state.update_events(until, parameters)
+    }
+
+    pub fn get_next_event(&self) -> Option<TimeFloat> {
+        self.events.peek().copied()
+    }
+
     // pub fn get_position<F: ValidId<Freighter>, S: ValidId<Star>>(
     //     &self,
     //     id: F,
@@ -0,0 +1,219 @@
+//! Procedural generation of a neighbourhood of stars, each with its own star system
+
+use super::generator::generate_star_system_with;
+use super::*;
+use crate::HashSet;
+use rand::{Rng, SeedableRng};
+use wyhash::WyRng;
+
+/// Places stars evenly within a sphere sized to hold them at the given density
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub struct GalaxyGenerator {
+    pub seed: u64,
+    pub star_count: usize,
+    /// Stars per cubic light year
+    #[serde(default = "solar_neighbourhood_density")]
+    pub density: f64,
+}
+
+impl GalaxyGenerator {
+    /// Roughly 0.14 stars per cubic parsec, as observed within a few hundred light years of Sol
+    pub const SOLAR_NEIGHBOURHOOD_DENSITY: f64 = 0.004;
+
+    pub fn new(seed: u64, star_count: usize) -> Self {
+        Self {
+            seed,
+            star_count,
+            density: Self::SOLAR_NEIGHBOURHOOD_DENSITY,
+        }
+    }
+
+    /// In light years
+    pub fn get_radius(&self) -> f64 {
+        let volume = self.star_count as f64 / self.density;
+        (volume * 3.0 / (4.0 * PI)).cbrt()
+    }
+
+    /// Generates the same stars and star systems for the same seed
+    pub fn generate(&self) -> Vec<StarSystem> {
+        let rng = &mut WyRng::seed_from_u64(self.seed);
+        let radius = self.get_radius();
+        let mut names = HashSet::default();
+
+        (0..self.star_count)
+            .map(|_| {
+                let star = Star {
+                    name: generate_name(&mut names, rng),
+                    position: generate_position(radius, rng),
+                    star_type: generate_star_type(rng),
+                };
+
+                generate_star_system_with(star, rng)
+            })
+            .collect()
+    }
+}
+
+fn solar_neighbourhood_density() -> f64 {
+    GalaxyGenerator::SOLAR_NEIGHBOURHOOD_DENSITY
+}
+
+/// Uniform within a sphere of the given radius in light years.
+///
+/// Without the `three_dimensional` feature, the sphere is flattened onto the galactic plane,
+/// so that a seed places its stars at the same x and y either way.
+fn generate_position<R: Rng>(radius: f64, rng: &mut R) -> GalaxyPosition {
+    loop {
+        let x = rng.gen_range(-1.0, 1.0);
+        let y = rng.gen_range(-1.0, 1.0);
+        let z = rng.gen_range(-1.0, 1.0);
+
+        if x * x + y * y + z * z <= 1.0 {
+            return GalaxyPosition::in_ly_3d(x * radius, y * radius, z * radius);
+        }
+    }
+}
+
+/// The share of each kind of star in the solar neighbourhood, from the RECONS census
+const STELLAR_POPULATION: [(fn(Fraction) -> StarType, f64); 10] = [
+    (StarType::O, 0.000_000_3),
+    (StarType::B, 0.001),
+    (StarType::A, 0.006),
+    (StarType::F, 0.03),
+    (StarType::G, 0.07),
+    (StarType::K, 0.12),
+    (StarType::M, 0.70),
+    (StarType::RedGiant, 0.004),
+    (StarType::WhiteDwarf, 0.068),
+    (|_| StarType::NeutronStar, 0.000_7),
+];
+
+fn generate_star_type<R: Rng>(rng: &mut R) -> StarType {
+    let total = STELLAR_POPULATION
+        .iter()
+        .map(|(_, share)| share)
+        .sum::<f64>();
+    let mut roll = rng.gen_range(0.0, total);
+
+    // smaller stars are more common, so skew each class towards its cool end
+    let fraction = Fraction::clamp(rng.gen_range(0.0f64, 1.0).powi(2));
+
+    for (star_type, share) in STELLAR_POPULATION.iter() {
+        if roll < *share {
+            return star_type(fraction);
+        }
+        roll -= share;
+    }
+
+    StarType::M(fraction)
+}
+
+const SYLLABLES: [&str; 24] = [
+    "al", "ba", "cor", "da", "el", "fen", "ga", "hel", "ir", "ka", "lo", "mar", "ne", "or", "pra",
+    "qui", "ra", "sol", "ta", "ul", "ve", "xi", "yo", "zen",
+];
+
+/// Tries for an unused name before falling back to a numbered one
+const NAME_ATTEMPTS: usize = 100;
+
+/// A pronounceable name that no other generated star has.
+///
+/// There are only 24² + 24³ + 24⁴ syllable names, so once they are mostly taken,
+/// a number is added to a taken name instead.
+fn generate_name<R: Rng>(names: &mut HashSet<String>, rng: &mut R) -> String {
+    for _ in 0..NAME_ATTEMPTS {
+        let name = generate_syllables(rng);
+
+        if names.insert(name.clone()) {
+            return name;
+        }
+    }
+
+    let syllables = generate_syllables(rng);
+    let name = (2..)
+        .map(|number| format!("{} {}", syllables, number))
+        .find(|name| !names.contains(name))
+        .unwrap();
+
+    names.insert(name.clone());
+    name
+}
+
+fn generate_syllables<R: Rng>(rng: &mut R) -> String {
+    let length = rng.gen_range(2, 5);
+    let mut name = (0..length)
+        .map(|_| SYLLABLES[rng.gen_range(0, SYLLABLES.len())])
+        .collect::<String>();
+
+    name[..1].make_ascii_uppercase();
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_generates_same_galaxy() {
+        let a = GalaxyGenerator::new(3, 20).generate();
+        let b = GalaxyGenerator::new(3, 20).generate();
+
+        assert_eq!(format!("{:?}", a), format!("{:?}", b));
+    }
+
+    #[test]
+    fn stars_are_within_radius_and_uniquely_named() {
+        let generator = GalaxyGenerator::new(0, 500);
+        let systems = generator.generate();
+        let radius = (GalaxyPosition::in_ly(generator.get_radius(), 0.0)
+            - GalaxyPosition::default())
+        .magnitude();
+
+        assert_eq!(500, systems.len());
+
+        let mut names = HashSet::default();
+        for system in &systems {
+            let distance = (system.star.position - GalaxyPosition::default()).magnitude();
+
+            assert!(distance <= radius * 1.000_001);
+            assert!(names.insert(system.star.name.as_str()));
+        }
+
+        let red_dwarfs = systems
+            .iter()
+            .filter(|system| matches!(system.star.star_type, StarType::M(_)))
+            .count();
+
+        assert!(red_dwarfs > 300);
+    }
+
+    #[test]
+    fn names_are_numbered_once_syllables_run_out() {
+        let mut names = HashSet::default();
+        let mut prefixes = vec![String::new()];
+
+        for length in 1..=4 {
+            prefixes = prefixes
+                .iter()
+                .flat_map(|prefix| SYLLABLES.iter().map(move |s| format!("{}{}", prefix, s)))
+                .collect();
+
+            if length >= 2 {
+                for name in &prefixes {
+                    let mut name = name.clone();
+                    name[..1].make_ascii_uppercase();
+                    names.insert(name);
+                }
+            }
+        }
+
+        assert_eq!(346_176, names.len());
+
+        let rng = &mut WyRng::seed_from_u64(0);
+        let first = generate_name(&mut names, rng);
+        let second = generate_name(&mut names, rng);
+
+        assert!(first.ends_with(char::is_numeric));
+        assert_ne!(first, second);
+    }
+}
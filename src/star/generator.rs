@@ -0,0 +1,414 @@
+//! Procedural generation of planets and moons for a star
+
+use super::*;
+use crate::body::*;
+use crate::constants::G;
+use rand::{Rng, SeedableRng};
+use wyhash::WyRng;
+
+const EARTH_MASS: Mass = Mass::in_kg(5.972e24);
+const EARTH_RADIUS: Length = Length::in_m(6.371e6);
+
+/// Gas giants hold on to their moons far beyond their surface, rocky planets less so
+const MAX_MOONS: usize = 6;
+
+/// Generates the same star system for the same star and seed
+pub fn generate_star_system(star: Star, seed: u64) -> StarSystem {
+    let rng = &mut WyRng::seed_from_u64(seed);
+    generate_star_system_with(star, rng)
+}
+
+pub fn generate_star_system_with<R: Rng>(star: Star, rng: &mut R) -> StarSystem {
+    let star_type = star.star_type;
+    let frost_line = get_frost_line(star_type);
+
+    let planet_count = rng.gen_range(1, 11);
+    let mut radius = get_inner_orbit(star_type) * rng.gen_range(1.0, 2.0);
+
+    let planets = (0..planet_count)
+        .map(|index| {
+            let name = format!("{} {}", star.name, get_planet_letter(index));
+            let kind = PlanetKind::generate(radius > frost_line, rng);
+            let planet = generate_planet(name, kind, radius, star_type, rng);
+
+            radius = radius * rng.gen_range(1.4, 2.2);
+
+            planet
+        })
+        .collect();
+
+    StarSystem { star, planets }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum PlanetKind {
+    Rocky,
+    Icy,
+    GasGiant,
+}
+
+impl PlanetKind {
+    /// Gas giants and icy bodies form where volatiles condense, beyond the frost line
+    fn generate<R: Rng>(beyond_frost_line: bool, rng: &mut R) -> Self {
+        match (beyond_frost_line, rng.gen_bool(0.6)) {
+            (false, _) => PlanetKind::Rocky,
+            (true, true) => PlanetKind::GasGiant,
+            (true, false) => PlanetKind::Icy,
+        }
+    }
+
+    /// In Earth masses, drawn evenly across orders of magnitude
+    fn generate_mass<R: Rng>(self, rng: &mut R) -> Mass {
+        let (min, max): (f64, f64) = match self {
+            PlanetKind::Rocky => (0.03, 5.0),
+            PlanetKind::Icy => (0.01, 2.0),
+            PlanetKind::GasGiant => (10.0, 600.0),
+        };
+
+        EARTH_MASS * rng.gen_range(min.ln(), max.ln()).exp()
+    }
+
+    fn get_radius(self, mass: Mass) -> Length {
+        let earth_masses = mass / EARTH_MASS;
+
+        match self {
+            PlanetKind::Rocky => EARTH_RADIUS * earth_masses.powf(0.27),
+            PlanetKind::Icy => EARTH_RADIUS * 1.1 * earth_masses.powf(0.3),
+            // gas giants are roughly the size of Jupiter regardless of mass
+            PlanetKind::GasGiant => EARTH_RADIUS * 11.2 * (earth_masses / 318.0).powf(-0.04),
+        }
+    }
+}
+
+fn generate_planet<R: Rng>(
+    name: String,
+    kind: PlanetKind,
+    radius: Length,
+    star_type: StarType,
+    rng: &mut R,
+) -> Planet {
+    let mass = kind.generate_mass(rng);
+    let body_radius = kind.get_radius(mass);
+    let orbit = generate_orbit(star_type.get_mass(), radius, 0.1, rng);
+    let flux = star_type.get_flux(radius);
+
+    let body = Body {
+        name,
+        mass,
+        radius: body_radius,
+        orbit,
+        properties: generate_properties(kind, mass, body_radius, flux, rng),
+    };
+
+    let moons = generate_moons(&body, kind, flux, star_type.get_mass(), rng);
+
+    Planet { body, moons }
+}
+
+fn generate_moons<R: Rng>(
+    planet: &Body,
+    kind: PlanetKind,
+    flux: Flux,
+    star_mass: Mass,
+    rng: &mut R,
+) -> Vec<Body> {
+    let count = match kind {
+        PlanetKind::GasGiant => rng.gen_range(1, MAX_MOONS + 1),
+        PlanetKind::Rocky | PlanetKind::Icy if planet.mass > 0.3 * EARTH_MASS => {
+            rng.gen_range(0, 3)
+        }
+        PlanetKind::Rocky | PlanetKind::Icy => 0,
+    };
+
+    // moons beyond about a third of the Hill sphere are not stable
+    let hill_radius =
+        planet.orbit.get_periapsis_distance() * (planet.mass / (3.0 * star_mass)).powf(1.0 / 3.0);
+    let max_radius = hill_radius / 3.0;
+
+    let mut radius = planet.radius * rng.gen_range(3.0, 10.0);
+    let mut moons = Vec::with_capacity(count);
+
+    for index in 0..count {
+        if radius > max_radius {
+            break;
+        }
+
+        // moons form from the debris of the planet, so are at most a small fraction of its mass
+        let max_mass = (0.02 * planet.mass / EARTH_MASS).min(0.05);
+        let mass = EARTH_MASS
+            * rng
+                .gen_range(0.0001f64.ln(), max_mass.max(0.0002).ln())
+                .exp();
+        let moon_kind = match kind {
+            PlanetKind::GasGiant => PlanetKind::Icy,
+            kind => kind,
+        };
+        let moon_radius = moon_kind.get_radius(mass);
+
+        moons.push(Body {
+            name: format!("{} {}", planet.name, get_moon_numeral(index)),
+            mass,
+            radius: moon_radius,
+            orbit: generate_orbit(planet.mass, radius, 0.05, rng),
+            properties: generate_properties(moon_kind, mass, moon_radius, flux, rng),
+        });
+
+        radius = radius * rng.gen_range(1.5, 2.5);
+    }
+
+    moons
+}
+
+fn generate_orbit<R: Rng>(
+    parent_mass: Mass,
+    radius: Length,
+    max_eccentricity: f64,
+    rng: &mut R,
+) -> Orbit {
+    Orbit {
+        radius,
+        angular_speed: AngularSpeed::of_orbit(parent_mass, radius),
+        offset: rng.gen(),
+        eccentricity: rng.gen_range(0.0, max_eccentricity),
+        periapsis: rng.gen(),
+        inclination: Angle::in_deg(rng.gen_range(0.0, 3.0)),
+        ascending_node: rng.gen(),
+    }
+}
+
+/// Derives the conditions of a body from its mass, size and the light it receives
+fn generate_properties<R: Rng>(
+    kind: PlanetKind,
+    mass: Mass,
+    radius: Length,
+    flux: Flux,
+    rng: &mut R,
+) -> BodyProperties {
+    if kind == PlanetKind::GasGiant {
+        return BodyProperties {
+            surface: Surface::Gaseous,
+            pressure: Pressure::Crushing,
+            oxygen: AtmosphericOxygen::None,
+            hydrosphere: Hydrosphere::None,
+            biosphere: Biosphere::None,
+            magnetosphere: Magnetosphere::Present,
+        };
+    }
+
+    let earth_masses = mass / EARTH_MASS;
+    // that of a dark body, before the atmosphere has formed
+    let formation_temperature = Temperature::of_equilibrium(flux, 0.0);
+
+    // only larger bodies stay warm enough to keep a molten core
+    let magnetosphere = if earth_masses > 0.3 {
+        Magnetosphere::Present
+    } else {
+        Magnetosphere::Absent
+    };
+
+    let pressure = get_pressure(
+        mass,
+        radius,
+        earth_masses,
+        formation_temperature,
+        magnetosphere,
+    );
+
+    // the warmest surface the atmosphere allows, as barren rock reflects the least light
+    let barren = BodyProperties {
+        surface: Surface::Barren,
+        pressure,
+        oxygen: AtmosphericOxygen::None,
+        hydrosphere: Hydrosphere::None,
+        biosphere: Biosphere::None,
+        magnetosphere,
+    };
+    let temperature = barren.get_surface_temperature(flux);
+    let hydrosphere = get_hydrosphere(pressure, temperature);
+
+    let surface = match hydrosphere {
+        _ if pressure == Pressure::Crushing && temperature > 400.0 * K => Surface::Volcanic,
+        Hydrosphere::Frozen if kind == PlanetKind::Icy || temperature < 200.0 * K => {
+            Surface::Frozen
+        }
+        Hydrosphere::Dynamic if rng.gen_bool(0.3) => Surface::Oceanic,
+        Hydrosphere::Dynamic => Surface::Continental {
+            land: Fraction::clamp(rng.gen_range(0.1, 0.6)),
+        },
+        Hydrosphere::Frozen | Hydrosphere::None => Surface::Barren,
+    };
+
+    // oceans and land reflect more light than bare rock, which may freeze the water over
+    let (surface, hydrosphere) = match surface {
+        Surface::Oceanic | Surface::Continental { .. } => {
+            let wet = BodyProperties {
+                surface,
+                hydrosphere,
+                ..barren
+            };
+
+            match get_hydrosphere(pressure, wet.get_surface_temperature(flux)) {
+                Hydrosphere::Dynamic => (surface, hydrosphere),
+                _ => (Surface::Frozen, Hydrosphere::Frozen),
+            }
+        }
+        _ => (surface, hydrosphere),
+    };
+
+    // life needs liquid water, a stable atmosphere, and protection from the stellar wind
+    let can_support_life = hydrosphere == Hydrosphere::Dynamic
+        && (pressure == Pressure::Ideal || pressure == Pressure::High)
+        && magnetosphere == Magnetosphere::Present;
+
+    let (biosphere, oxygen) = match rng.gen_range(0.0, 1.0) {
+        roll if can_support_life && roll < 0.1 => (Biosphere::Advanced, AtmosphericOxygen::Ideal),
+        roll if can_support_life && roll < 0.3 => {
+            (Biosphere::Primordial, AtmosphericOxygen::Partial)
+        }
+        _ => (Biosphere::None, AtmosphericOxygen::None),
+    };
+
+    BodyProperties {
+        surface,
+        pressure,
+        oxygen,
+        hydrosphere,
+        biosphere,
+        magnetosphere,
+    }
+}
+
+/// Without a magnetosphere the stellar wind strips all but the densest atmospheres
+fn get_pressure(
+    mass: Mass,
+    radius: Length,
+    earth_masses: f64,
+    temperature: Temperature,
+    magnetosphere: Magnetosphere,
+) -> Pressure {
+    let escape_speed = Speed::in_m_per_s((2.0 * G * mass.value / radius.value).sqrt());
+
+    if escape_speed < Speed::in_m_per_s(5e3) {
+        return Pressure::Vacuum;
+    }
+
+    match magnetosphere {
+        // a runaway greenhouse on a massive planet, e.g., Venus
+        Magnetosphere::Absent if earth_masses > 0.7 && temperature > 300.0 * K => {
+            Pressure::Crushing
+        }
+        Magnetosphere::Absent => Pressure::Thin,
+        Magnetosphere::Present if earth_masses < 0.5 => Pressure::Thin,
+        Magnetosphere::Present if earth_masses < 2.0 => Pressure::Ideal,
+        Magnetosphere::Present if earth_masses < 5.0 => Pressure::High,
+        Magnetosphere::Present => Pressure::Crushing,
+    }
+}
+
+/// Water is liquid only under an atmosphere and within a narrow band of surface temperatures
+fn get_hydrosphere(pressure: Pressure, temperature: Temperature) -> Hydrosphere {
+    match pressure {
+        Pressure::Vacuum if temperature < 150.0 * K => Hydrosphere::Frozen,
+        Pressure::Vacuum => Hydrosphere::None,
+        _ if temperature < 240.0 * K => Hydrosphere::Frozen,
+        Pressure::Thin | Pressure::Crushing => Hydrosphere::None,
+        _ if temperature < 330.0 * K => Hydrosphere::Dynamic,
+        _ => Hydrosphere::None,
+    }
+}
+
+/// Where water ice condenses during system formation
+fn get_frost_line(star_type: StarType) -> Length {
+    let luminosity = star_type.get_luminosity() / SOLAR_LUMINOSITY;
+    2.7 * AU * luminosity.sqrt()
+}
+
+/// Scales Mercury's orbit with the mass of the star
+fn get_inner_orbit(star_type: StarType) -> Length {
+    let mass = star_type.get_mass() / SOLAR_MASS;
+    0.2 * AU * mass
+}
+
+fn get_planet_letter(index: usize) -> char {
+    (b'b' + index as u8) as char
+}
+
+fn get_moon_numeral(index: usize) -> &'static str {
+    const NUMERALS: [&str; MAX_MOONS] = ["I", "II", "III", "IV", "V", "VI"];
+    NUMERALS[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::star::examples::sol;
+
+    #[test]
+    fn same_seed_generates_same_system() {
+        let a = generate_star_system(sol(), 7);
+        let b = generate_star_system(sol(), 7);
+        let c = generate_star_system(sol(), 8);
+
+        assert_eq!(format!("{:?}", a), format!("{:?}", b));
+        assert_ne!(format!("{:?}", a), format!("{:?}", c));
+    }
+
+    #[test]
+    fn generated_systems_are_plausible() {
+        for seed in 0..50 {
+            let system = generate_star_system(sol(), seed);
+            let frost_line = get_frost_line(system.star.star_type);
+
+            assert!(!system.planets.is_empty());
+
+            for pair in system.planets.windows(2) {
+                assert!(pair[0].body.orbit.radius < pair[1].body.orbit.radius);
+            }
+
+            for planet in &system.planets {
+                let body = &planet.body;
+
+                if body.properties.surface == Surface::Gaseous {
+                    assert!(body.orbit.radius > frost_line);
+                }
+
+                if body.properties.hydrosphere == Hydrosphere::Dynamic {
+                    assert_ne!(Pressure::Vacuum, body.properties.pressure);
+
+                    let flux = system.star.star_type.get_flux(body.orbit.radius);
+                    let temperature = body.properties.get_surface_temperature(flux);
+                    assert!(temperature >= 240.0 * K && temperature < 330.0 * K);
+                }
+
+                for moon in &planet.moons {
+                    assert!(moon.mass < body.mass);
+                    assert!(moon.orbit.radius > body.radius);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn atmospheres_depend_on_gravity_and_magnetosphere() {
+        let rng = &mut WyRng::seed_from_u64(0);
+        let mut generate = |earth_masses: f64| {
+            let mass = earth_masses * EARTH_MASS;
+            let radius = PlanetKind::Rocky.get_radius(mass);
+            generate_properties(PlanetKind::Rocky, mass, radius, Flux::SOLAR_CONSTANT, rng)
+        };
+
+        // too small to hold an atmosphere
+        let small = generate(0.05);
+        assert_eq!(Pressure::Vacuum, small.pressure);
+
+        // stripped by the stellar wind
+        let unshielded = generate(0.2);
+        assert_eq!(Magnetosphere::Absent, unshielded.magnetosphere);
+        assert_eq!(Pressure::Thin, unshielded.pressure);
+
+        let earth_like = generate(1.0);
+        assert_eq!(Magnetosphere::Present, earth_like.magnetosphere);
+        assert_eq!(Pressure::Ideal, earth_like.pressure);
+        assert_eq!(Hydrosphere::Dynamic, earth_like.hydrosphere);
+    }
+}
@@ -0,0 +1,286 @@
+//! A spatial index over fixed positions, such as those of stars
+
+use crate::components::{GalaxyPosition, Length};
+use serde::{Deserialize, Serialize};
+
+/// A k-d tree that splits space along x, y and, with the `three_dimensional` feature, z in turn.
+///
+/// Values are added one at a time and never moved, so the tree is only balanced if positions
+/// are not inserted in sorted order. Randomly placed stars give a tree of expected depth O(log n).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdTree<T> {
+    nodes: Vec<Node<T>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node<T> {
+    position: GalaxyPosition,
+    value: T,
+    children: [Option<usize>; 2],
+}
+
+impl<T> Default for KdTree<T> {
+    fn default() -> Self {
+        Self { nodes: Vec::new() }
+    }
+}
+
+impl<T: Copy> KdTree<T> {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn insert(&mut self, position: GalaxyPosition, value: T) {
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            position,
+            value,
+            children: [None, None],
+        });
+
+        if index == 0 {
+            return;
+        }
+
+        let mut parent = 0;
+        let mut depth = 0;
+
+        loop {
+            let side = get_side(&self.nodes[parent].position, &position, depth);
+
+            match self.nodes[parent].children[side] {
+                Some(child) => {
+                    parent = child;
+                    depth += 1;
+                }
+                None => {
+                    self.nodes[parent].children[side] = Some(index);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// The value closest to the position
+    pub fn nearest(&self, position: GalaxyPosition) -> Option<T> {
+        self.k_nearest(position, 1).into_iter().next()
+    }
+
+    /// Up to `k` values, nearest first
+    pub fn k_nearest(&self, position: GalaxyPosition, k: usize) -> Vec<T> {
+        let mut nearest = Nearest {
+            k,
+            found: Vec::with_capacity(k + 1),
+        };
+
+        if k > 0 && !self.is_empty() {
+            self.search_nearest(0, 0, &position, &mut nearest);
+        }
+
+        nearest
+            .found
+            .into_iter()
+            .map(|(_, index)| self.nodes[index].value)
+            .collect()
+    }
+
+    /// Every value within the radius of the position, in no particular order
+    pub fn within(&self, position: GalaxyPosition, radius: Length) -> Vec<T> {
+        let mut found = Vec::new();
+
+        if !self.is_empty() {
+            let radius_squared = radius.value * radius.value;
+            self.search_within(0, 0, &position, radius_squared, &mut found);
+        }
+
+        found
+    }
+
+    fn search_nearest(
+        &self,
+        index: usize,
+        depth: usize,
+        target: &GalaxyPosition,
+        nearest: &mut Nearest,
+    ) {
+        let node = &self.nodes[index];
+        nearest.offer(get_distance_squared(&node.position, target), index);
+
+        let side = get_side(&node.position, target, depth);
+        let offset = get_axis(target, depth) - get_axis(&node.position, depth);
+
+        if let Some(near) = node.children[side] {
+            self.search_nearest(near, depth + 1, target, nearest);
+        }
+
+        // the far side can only hold closer values if the splitting plane is closer than the worst found
+        if let Some(far) = node.children[1 - side] {
+            if nearest.accepts(offset * offset) {
+                self.search_nearest(far, depth + 1, target, nearest);
+            }
+        }
+    }
+
+    fn search_within(
+        &self,
+        index: usize,
+        depth: usize,
+        target: &GalaxyPosition,
+        radius_squared: f64,
+        found: &mut Vec<T>,
+    ) {
+        let node = &self.nodes[index];
+
+        if get_distance_squared(&node.position, target) <= radius_squared {
+            found.push(node.value);
+        }
+
+        let side = get_side(&node.position, target, depth);
+        let offset = get_axis(target, depth) - get_axis(&node.position, depth);
+
+        if let Some(near) = node.children[side] {
+            self.search_within(near, depth + 1, target, radius_squared, found);
+        }
+
+        if let Some(far) = node.children[1 - side] {
+            if offset * offset <= radius_squared {
+                self.search_within(far, depth + 1, target, radius_squared, found);
+            }
+        }
+    }
+}
+
+/// The closest nodes found so far, sorted by their squared distance in meters
+struct Nearest {
+    k: usize,
+    found: Vec<(f64, usize)>,
+}
+
+impl Nearest {
+    fn accepts(&self, distance_squared: f64) -> bool {
+        self.found.len() < self.k
+            || self
+                .found
+                .last()
+                .map_or(true, |(worst, _)| distance_squared < *worst)
+    }
+
+    fn offer(&mut self, distance_squared: f64, index: usize) {
+        if !self.accepts(distance_squared) {
+            return;
+        }
+
+        let position = self
+            .found
+            .iter()
+            .position(|(d, _)| distance_squared < *d)
+            .unwrap_or_else(|| self.found.len());
+
+        self.found.insert(position, (distance_squared, index));
+        self.found.truncate(self.k);
+    }
+}
+
+/// The number of axes that the tree splits along
+const AXES: usize = if cfg!(feature = "three_dimensional") {
+    3
+} else {
+    2
+};
+
+fn get_axis(position: &GalaxyPosition, depth: usize) -> f64 {
+    match depth % AXES {
+        0 => position.x.value,
+        #[cfg(feature = "three_dimensional")]
+        2 => position.z.value,
+        _ => position.y.value,
+    }
+}
+
+/// 0 for the lower half of the node's splitting plane, 1 for the upper half
+fn get_side(node: &GalaxyPosition, position: &GalaxyPosition, depth: usize) -> usize {
+    if get_axis(position, depth) < get_axis(node, depth) {
+        0
+    } else {
+        1
+    }
+}
+
+fn get_distance_squared(a: &GalaxyPosition, b: &GalaxyPosition) -> f64 {
+    let distance = (*a - *b).magnitude().value;
+    distance * distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::LY;
+    use rand::{Rng, SeedableRng};
+    use wyhash::WyRng;
+
+    fn random_tree(count: usize) -> (KdTree<usize>, Vec<GalaxyPosition>) {
+        let rng = &mut WyRng::seed_from_u64(0);
+        let mut tree = KdTree::default();
+
+        let positions = (0..count)
+            .map(|_| {
+                GalaxyPosition::in_ly_3d(
+                    rng.gen_range(-50.0, 50.0),
+                    rng.gen_range(-50.0, 50.0),
+                    rng.gen_range(-50.0, 50.0),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (index, position) in positions.iter().enumerate() {
+            tree.insert(*position, index);
+        }
+
+        (tree, positions)
+    }
+
+    fn sorted_by_distance(positions: &[GalaxyPosition], target: GalaxyPosition) -> Vec<usize> {
+        let mut indices = (0..positions.len()).collect::<Vec<_>>();
+        indices.sort_by(|a, b| {
+            let a = get_distance_squared(&positions[*a], &target);
+            let b = get_distance_squared(&positions[*b], &target);
+            a.partial_cmp(&b).unwrap()
+        });
+        indices
+    }
+
+    #[test]
+    fn queries_match_a_linear_scan() {
+        let (tree, positions) = random_tree(500);
+        let target = GalaxyPosition::in_ly_3d(3.0, -7.0, 12.0);
+        let expected = sorted_by_distance(&positions, target);
+
+        assert_eq!(Some(expected[0]), tree.nearest(target));
+        assert_eq!(expected[..10].to_vec(), tree.k_nearest(target, 10));
+
+        let radius = 20.0 * LY;
+        let mut within = tree.within(target, radius);
+        within.sort_by_key(|index| expected.iter().position(|i| i == index));
+
+        let expected_within = expected
+            .into_iter()
+            .take_while(|i| get_distance_squared(&positions[*i], &target) <= radius.value.powi(2))
+            .collect::<Vec<_>>();
+
+        assert!(!expected_within.is_empty());
+        assert_eq!(expected_within, within);
+    }
+
+    #[test]
+    fn empty_tree_finds_nothing() {
+        let tree = KdTree::<usize>::default();
+
+        assert_eq!(None, tree.nearest(GalaxyPosition::default()));
+        assert!(tree.k_nearest(GalaxyPosition::default(), 3).is_empty());
+        assert!(tree.within(GalaxyPosition::default(), LY).is_empty());
+    }
+}
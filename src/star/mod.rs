@@ -1,33 +1,50 @@
 use crate::body::{Body, Planet};
+use crate::constants::STEFAN_BOLTZMANN;
+use crate::star::kd_tree::KdTree;
 use crate::state::State;
 use crate::*;
-use iter_context::ContextualIterator;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
 use std::fmt::{Display, Formatter, Result};
+use std::ops::Range;
 
-#[derive(Debug, Clone)]
+pub mod galaxy;
+pub mod generator;
+pub mod kd_tree;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Star {
     pub name: String,
-    pub position: Position,
+    pub position: GalaxyPosition,
     pub star_type: StarType,
 }
 
 fixed_arena!(Star);
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Stars {
     pub alloc: Allocator<Star>,
 
     pub name: Component<Star, String>,
-    pub position: Component<Star, Position>,
+    pub position: Component<Star, GalaxyPosition>,
     pub star_type: Component<Star, StarType>,
 
     bodies: Component<Star, Vec<Id<Body>>>,
+
+    /// Stars are never moved, so their positions are indexed once when they are created
+    #[serde(skip)]
+    index: KdTree<Id<Star>>,
+    #[serde(skip)]
+    names: HashMap<String, Id<Star>>,
 }
 
 impl Stars {
     pub fn create(&mut self, row: Star) -> Id<Star> {
         let id = self.alloc.create();
 
+        self.names.entry(row.name.clone()).or_insert(id);
+        self.index.insert(row.position, id);
+
         self.name.insert(id, row.name);
         self.position.insert(id, row.position);
         self.star_type.insert(id, row.star_type);
@@ -36,26 +53,47 @@ impl Stars {
         id
     }
 
+    /// Rebuilds the lookups by name and position, which are not saved
+    pub fn rebuild_index(&mut self) {
+        self.index = KdTree::default();
+        self.names.clear();
+
+        for id in self.alloc.ids() {
+            self.names.entry(self.name.get(id).clone()).or_insert(id);
+            self.index.insert(*self.position.get(id), id);
+        }
+    }
+
     #[inline]
     pub fn get_radius<I: ValidId<Star>>(&self, id: I) -> Length {
         self.star_type.get(id).get_radius()
     }
 
+    /// The first star created with the name
     pub fn get_by_name(&self, name: &str) -> Option<Id<Star>> {
-        self.name
-            .iter()
-            .zip(self.alloc.ids())
-            .into_iter()
-            .filter_map(
-                |(star_name, id)| {
-                    if star_name.eq(name) {
-                        Some(id)
-                    } else {
-                        None
-                    }
-                },
-            )
-            .next()
+        self.names.get(name).copied()
+    }
+
+    pub fn get_nearest(&self, position: GalaxyPosition) -> Option<Id<Star>> {
+        self.index.nearest(position)
+    }
+
+    /// Up to `k` stars, nearest first, including any star at the position itself
+    pub fn get_k_nearest(&self, position: GalaxyPosition, k: usize) -> Vec<Id<Star>> {
+        self.index.k_nearest(position, k)
+    }
+
+    /// Every star within the radius, including any star at the position itself
+    pub fn get_within(&self, position: GalaxyPosition, radius: Length) -> Vec<Id<Star>> {
+        self.index.within(position, radius)
+    }
+
+    pub fn get_habitable_zone<I: ValidId<Star>>(&self, id: I) -> Range<Length> {
+        self.star_type.get(id).get_habitable_zone()
+    }
+
+    pub fn is_in_habitable_zone<I: ValidId<Star>>(&self, id: I, distance: Length) -> bool {
+        self.get_habitable_zone(id).contains(&distance)
     }
 
     pub fn bodies(&self, star: Id<Star>) -> &Vec<Id<Body>> {
@@ -63,7 +101,7 @@ impl Stars {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StarSystem {
     pub star: Star,
     pub planets: Vec<Planet>,
@@ -80,15 +118,90 @@ impl State {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+/// The spectral type of a star.
+///
+/// The fraction places the star within its class, from the cool end at zero to the hot end at one,
+/// so that `G(1.0)` is a G0 star and `G(0.0)` is just short of K0.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum StarType {
+    O(Fraction),
+    B(Fraction),
+    A(Fraction),
+    F(Fraction),
     G(Fraction),
+    K(Fraction),
+    M(Fraction),
+    /// A luminosity class III star, from the cool and large to the hot and compact
+    RedGiant(Fraction),
+    /// From old and cool to young and hot
+    WhiteDwarf(Fraction),
+    NeutronStar,
+}
+
+/// Main-sequence properties at subclass 0 of each class, from hottest to coolest
+#[derive(Debug, Copy, Clone)]
+struct ClassBounds {
+    letter: char,
+    temperature: f64,
+    /// Solar masses
+    mass: f64,
+    /// Solar radii
+    radius: f64,
+}
+
+/// Each class ends where the next begins, with the last entry marking the cool end of class M
+const MAIN_SEQUENCE: [ClassBounds; 8] = [
+    ClassBounds::new('O', 50_000.0, 60.0, 12.0),
+    ClassBounds::new('B', 31_400.0, 17.7, 7.16),
+    ClassBounds::new('A', 9_700.0, 2.18, 2.19),
+    ClassBounds::new('F', 7_220.0, 1.61, 1.73),
+    ClassBounds::new('G', 5_920.0, 1.06, 1.10),
+    ClassBounds::new('K', 5_280.0, 0.88, 0.813),
+    ClassBounds::new('M', 3_850.0, 0.57, 0.588),
+    ClassBounds::new('M', 2_300.0, 0.075, 0.10),
+];
+
+impl ClassBounds {
+    const fn new(letter: char, temperature: f64, mass: f64, radius: f64) -> Self {
+        Self {
+            letter,
+            temperature,
+            mass,
+            radius,
+        }
+    }
 }
 
 impl StarType {
+    /// The index of the class in `MAIN_SEQUENCE`, and the position within it
+    fn get_main_sequence(self) -> Option<(usize, Fraction)> {
+        match self {
+            StarType::O(fraction) => Some((0, fraction)),
+            StarType::B(fraction) => Some((1, fraction)),
+            StarType::A(fraction) => Some((2, fraction)),
+            StarType::F(fraction) => Some((3, fraction)),
+            StarType::G(fraction) => Some((4, fraction)),
+            StarType::K(fraction) => Some((5, fraction)),
+            StarType::M(fraction) => Some((6, fraction)),
+            StarType::RedGiant(_) | StarType::WhiteDwarf(_) | StarType::NeutronStar => None,
+        }
+    }
+
+    /// Interpolates a property between the hot end of the star's class and the start of the next
+    fn interpolate_main_sequence(self, value: fn(&ClassBounds) -> f64) -> f64 {
+        let (index, fraction) = self.get_main_sequence().expect("not a main-sequence star");
+
+        let hot = value(&MAIN_SEQUENCE[index]);
+        let cool = value(&MAIN_SEQUENCE[index + 1]);
+        interpolate(fraction, cool, hot)
+    }
+
     pub fn get_temperature(self) -> Temperature {
         let kelvin = match self {
-            StarType::G(fraction) => 5e3 + fraction * 1e3,
+            StarType::RedGiant(fraction) => interpolate(fraction, 3_300.0, 5_100.0),
+            StarType::WhiteDwarf(fraction) => interpolate(fraction, 4_000.0, 40_000.0),
+            StarType::NeutronStar => 1e6,
+            main_sequence => main_sequence.interpolate_main_sequence(|class| class.temperature),
         };
 
         Temperature::in_k(kelvin)
@@ -96,7 +209,14 @@ impl StarType {
 
     pub fn get_radius(self) -> Length {
         let solar_fraction: f64 = match self {
-            StarType::G(fraction) => 0.85 + fraction * 0.3,
+            StarType::RedGiant(fraction) => interpolate(fraction, 100.0, 10.0),
+            // white dwarfs shrink as their mass increases
+            StarType::WhiteDwarf(_) => {
+                let mass = self.get_mass() / SOLAR_MASS;
+                0.0126 * mass.powf(-1.0 / 3.0)
+            }
+            StarType::NeutronStar => return 12.0 * KM,
+            main_sequence => main_sequence.interpolate_main_sequence(|class| class.radius),
         };
 
         SOLAR_RADIUS * solar_fraction
@@ -104,23 +224,97 @@ impl StarType {
 
     pub fn get_mass(self) -> Mass {
         let solar_fraction: f64 = match self {
-            StarType::G(fraction) => 0.85 + fraction * 0.3,
+            StarType::RedGiant(fraction) => interpolate(fraction, 0.8, 3.0),
+            StarType::WhiteDwarf(fraction) => interpolate(fraction, 0.5, 0.9),
+            StarType::NeutronStar => 1.4,
+            main_sequence => main_sequence.interpolate_main_sequence(|class| class.mass),
         };
 
         SOLAR_MASS * solar_fraction
     }
+
+    /// The power radiated by a black body of the star's radius and temperature
+    pub fn get_luminosity(self) -> Power {
+        let radius = self.get_radius().value;
+        let temperature = self.get_temperature().value;
+        let area = 4.0 * PI * radius * radius;
+
+        Power::in_watts(area * STEFAN_BOLTZMANN * temperature.powi(4))
+    }
+
+    /// The power received per unit area at the given distance from the star
+    pub fn get_flux(self, distance: Length) -> Flux {
+        self.get_luminosity() / Area::of_sphere(distance)
+    }
+
+    /// The distances at which an Earth-like planet could keep liquid water on its surface.
+    ///
+    /// Uses the conservative bounds of Kopparapu et al. (2013) for a Sun-like star, from the runaway
+    /// greenhouse at 1.0385 times Earth's flux to the maximum greenhouse at 0.3507 times Earth's flux.
+    pub fn get_habitable_zone(self) -> Range<Length> {
+        let distance = |relative_flux: f64| {
+            let flux = Flux::SOLAR_CONSTANT * relative_flux;
+            let area = self.get_luminosity() / flux;
+            Length::in_m((area.value / (4.0 * PI)).sqrt())
+        };
+
+        distance(1.0385)..distance(0.3507)
+    }
+}
+
+/// Geometric interpolation, returning `zero` at a fraction of zero and `one` at a fraction of one
+fn interpolate(fraction: Fraction, zero: f64, one: f64) -> f64 {
+    zero * (one / zero).powf(fraction.value())
+}
+
+/// Subclass 0 is the hot end of a class and 9 the cool end
+fn get_subclass(fraction: Fraction) -> u8 {
+    ((1.0 - fraction.value()) * 10.0).floor().min(9.0) as u8
+}
+
+/// The main-sequence class and subclass with the given temperature
+fn get_spectral_class(temperature: Temperature) -> (char, u8) {
+    let kelvin = temperature.value;
+
+    for bounds in MAIN_SEQUENCE.windows(2) {
+        let (hot, cool) = (bounds[0], bounds[1]);
+
+        if kelvin >= cool.temperature {
+            let fraction =
+                (kelvin / cool.temperature).ln() / (hot.temperature / cool.temperature).ln();
+            return (hot.letter, get_subclass(Fraction::clamp(fraction)));
+        }
+    }
+
+    ('M', 9)
 }
 
 impl Display for StarType {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
-            StarType::G(_) => write!(f, "G-Type star"),
+            StarType::RedGiant(_) => {
+                let (letter, subclass) = get_spectral_class(self.get_temperature());
+                write!(f, "{}{}III", letter, subclass)
+            }
+            StarType::WhiteDwarf(_) => {
+                let index = (50_400.0 / self.get_temperature().value).round().max(1.0);
+                write!(f, "DA{}", index)
+            }
+            StarType::NeutronStar => write!(f, "Neutron star"),
+            main_sequence => {
+                let (index, fraction) = main_sequence
+                    .get_main_sequence()
+                    .expect("not a main-sequence star");
+                let letter = MAIN_SEQUENCE[index].letter;
+                write!(f, "{}{}V", letter, get_subclass(fraction))
+            }
         }
     }
 }
 
 const SOLAR_MASS: Mass = 1.9884e30 * KG;
 const SOLAR_RADIUS: Length = 695_700.0 * KM;
+const SOLAR_LUMINOSITY: Power = Power::in_watts(3.828e26);
 
 pub mod examples {
     use super::*;
@@ -129,7 +323,19 @@ pub mod examples {
     pub fn sol_system() -> StarSystem {
         StarSystem {
             star: sol(),
-            planets: vec![mercury(), venus(), earth(), mars()],
+            planets: vec![
+                mercury(),
+                venus(),
+                earth(),
+                mars(),
+                ceres(),
+                jupiter(),
+                saturn(),
+                uranus(),
+                neptune(),
+                pluto(),
+                eris(),
+            ],
         }
     }
 
@@ -137,7 +343,95 @@ pub mod examples {
         Star {
             name: "Sol".to_string(),
             position: Default::default(),
-            star_type: StarType::G(Fraction::clamp(0.5)),
+            // placed within G2 to give one solar luminosity
+            star_type: StarType::G(Fraction::clamp(0.725)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::examples::sol;
+    use super::*;
+
+    #[test]
+    fn sol_is_a_g2_dwarf() {
+        let star_type = sol().star_type;
+
+        assert_eq!("G2V", star_type.to_string());
+        assert!((star_type.get_temperature().value - 5772.0).abs() < 50.0);
+        assert!((star_type.get_mass() / SOLAR_MASS - 1.0).abs() < 0.05);
+        assert!((star_type.get_luminosity() / SOLAR_LUMINOSITY - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn main_sequence_is_ordered_by_temperature() {
+        let classes = [
+            StarType::O(Fraction::clamp(0.5)),
+            StarType::B(Fraction::clamp(0.5)),
+            StarType::A(Fraction::clamp(0.5)),
+            StarType::F(Fraction::clamp(0.5)),
+            StarType::G(Fraction::clamp(0.5)),
+            StarType::K(Fraction::clamp(0.5)),
+            StarType::M(Fraction::clamp(0.5)),
+        ];
+
+        for pair in classes.windows(2) {
+            assert!(pair[0].get_temperature() > pair[1].get_temperature());
+            assert!(pair[0].get_mass() > pair[1].get_mass());
+            assert!(pair[0].get_luminosity() > pair[1].get_luminosity());
+        }
+    }
+
+    #[test]
+    fn habitable_zone_of_sol_includes_earth_but_not_venus() {
+        let star_type = sol().star_type;
+        let habitable_zone = star_type.get_habitable_zone();
+
+        assert!(habitable_zone.contains(&(1.0 * AU)));
+        assert!(!habitable_zone.contains(&(0.723 * AU)));
+        assert!(!habitable_zone.contains(&(5.2 * AU)));
+
+        let relative_flux = star_type.get_flux(1.0 * AU) / Flux::SOLAR_CONSTANT;
+        assert!((relative_flux - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn stars_are_found_by_name_and_position() {
+        let mut state = State::default();
+        for system in galaxy::GalaxyGenerator::new(0, 200).generate() {
+            state.create_star_system(system);
+        }
+        state.create_star_system(examples::sol_system());
+
+        let stars = &state.star;
+        let sol = stars.get_by_name("Sol").unwrap();
+        let origin = *stars.position.get(sol);
+
+        assert_eq!(Some(sol), stars.get_nearest(origin));
+        assert_eq!(sol, stars.get_k_nearest(origin, 5)[0]);
+
+        let neighbours = stars.get_within(origin, 20.0 * LY);
+        let expected = stars
+            .alloc
+            .ids()
+            .into_iter()
+            .filter(|star| (*stars.position.get(*star) - origin).magnitude() <= 20.0 * LY)
+            .count();
+
+        assert!(neighbours.contains(&sol));
+        assert_eq!(expected, neighbours.len());
+    }
+
+    #[test]
+    fn display_names_class_and_subclass() {
+        assert_eq!("B0V", StarType::B(Fraction::ONE).to_string());
+        assert_eq!("M9V", StarType::M(Fraction::ZERO).to_string());
+        assert_eq!(
+            "K7III",
+            StarType::RedGiant(Fraction::clamp(0.5)).to_string()
+        );
+        assert_eq!("DA1", StarType::WhiteDwarf(Fraction::ONE).to_string());
+        assert_eq!("Neutron star", StarType::NeutronStar.to_string());
+    }
+}
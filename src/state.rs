@@ -1,11 +1,13 @@
 use crate::body::Bodies;
 use crate::colony::Colonies;
 // use crate::nation::Nations;
+use crate::components::TimeFloat;
 use crate::ships::Freighters;
 use crate::star::Stars;
 use crate::time::{DateTime, TimeState};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct State {
     pub time: TimeState,
     pub star: Stars,
@@ -22,6 +24,23 @@ impl State {
         }
     }
 
+    /// Rebuilds the lookups that are derived from other fields and left out of saves
+    pub fn rebuild_indices(&mut self) {
+        self.star.rebuild_index();
+        self.body.rebuild_index();
+    }
+
+    /// The time of the next one-off event scheduled by the simulation, if any
+    pub fn get_next_event(&self) -> Option<TimeFloat> {
+        self.freighter.get_next_event()
+    }
+
+    /// Runs every one-off event scheduled at or before the given time, returning how many ran
+    pub fn run_events(&mut self, until: TimeFloat) -> usize {
+        self.freighter
+            .update_events(until, &self.time, &mut self.colony, &self.body, &self.star)
+    }
+
     pub fn print(&self) {
         self.time.print();
         self.colony.print();
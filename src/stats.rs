@@ -0,0 +1,136 @@
+use crate::systems::{System, SystemArray};
+use crate::time::StdDuration;
+use std::fmt::{Display, Formatter, Result};
+
+/// Execution statistics of a single built-in system
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct SystemProfile {
+    pub runs: u64,
+    pub total_time: StdDuration,
+    pub max_time: StdDuration,
+    /// Summed over every run
+    pub entities: u64,
+}
+
+impl SystemProfile {
+    pub fn record(&mut self, time: StdDuration, entities: usize) {
+        self.runs += 1;
+        self.total_time += time;
+        self.max_time = self.max_time.max(time);
+        self.entities += entities as u64;
+    }
+
+    pub fn get_mean_time(&self) -> StdDuration {
+        if self.runs == 0 {
+            StdDuration::default()
+        } else {
+            StdDuration::from_secs_f64(self.total_time.as_secs_f64() / self.runs as f64)
+        }
+    }
+}
+
+/// Wall time spent in each built-in system, recorded by the `SystemQueue` while profiling is enabled
+#[derive(Debug, Clone)]
+pub struct SystemStats {
+    profiles: SystemArray<SystemProfile>,
+    /// One-off simulation events, such as freighter arrivals, which run between systems, one entity per event
+    events: SystemProfile,
+    /// Scheduled commands, one entity per command
+    commands: SystemProfile,
+}
+
+impl Default for SystemStats {
+    fn default() -> Self {
+        Self {
+            profiles: SystemArray::new([SystemProfile::default(); System::LEN]),
+            events: SystemProfile::default(),
+            commands: SystemProfile::default(),
+        }
+    }
+}
+
+impl SystemStats {
+    pub fn get(&self, system: System) -> &SystemProfile {
+        &self.profiles[system]
+    }
+
+    pub fn get_events(&self) -> &SystemProfile {
+        &self.events
+    }
+
+    pub fn get_commands(&self) -> &SystemProfile {
+        &self.commands
+    }
+
+    pub fn record(&mut self, system: System, time: StdDuration, entities: usize) {
+        self.profiles[system].record(time, entities);
+    }
+
+    pub fn record_events(&mut self, time: StdDuration, entities: usize) {
+        self.events.record(time, entities);
+    }
+
+    pub fn record_command(&mut self, time: StdDuration) {
+        self.commands.record(time, 1);
+    }
+
+    pub fn get_total_time(&self) -> StdDuration {
+        self.rows().map(|(_, profile)| profile.total_time).sum()
+    }
+
+    /// Each built-in system, followed by events and commands
+    fn rows(&self) -> impl Iterator<Item = (String, &SystemProfile)> {
+        let systems = System::ARRAY
+            .iter()
+            .map(move |system| (format!("{:?}", system), &self.profiles[*system]));
+
+        systems.chain(vec![
+            ("Events".to_string(), &self.events),
+            ("Commands".to_string(), &self.commands),
+        ])
+    }
+
+    /// One row per system, then rows for events and commands, with times in seconds
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("system,runs,total_s,mean_s,max_s,entities\n");
+
+        for (name, profile) in self.rows() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                name,
+                profile.runs,
+                profile.total_time.as_secs_f64(),
+                profile.get_mean_time().as_secs_f64(),
+                profile.max_time.as_secs_f64(),
+                profile.entities,
+            ));
+        }
+
+        csv
+    }
+}
+
+impl Display for SystemStats {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        writeln!(
+            f,
+            "{:<24}{:>10}{:>14}{:>14}{:>14}{:>12}",
+            "System", "Runs", "Total", "Mean", "Max", "Entities"
+        )?;
+
+        for (name, profile) in self.rows() {
+            writeln!(
+                f,
+                "{:<24}{:>10}{:>14}{:>14}{:>14}{:>12}",
+                name,
+                profile.runs,
+                format!("{:.3?}", profile.total_time),
+                format!("{:.3?}", profile.get_mean_time()),
+                format!("{:.3?}", profile.max_time),
+                profile.entities,
+            )?;
+        }
+
+        Ok(())
+    }
+}
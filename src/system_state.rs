@@ -1,12 +1,22 @@
+use crate::colony::{Colony, ColonyLinks};
+use crate::command::{Command, CommandId, Created};
 use crate::components::Duration;
+use crate::journal::{Journal, JournalEntry};
+use crate::ships::{Freighter, FreighterLinks};
 use crate::state::State;
-use crate::systems::SystemQueue;
-use crate::time::DateTime;
+use crate::systems::{CustomSystem, RegisterError, SystemKey, SystemQueue};
+use crate::time::{DateTime, TimeError};
+use gen_id::Id;
 
-#[derive(Debug, Default)]
+/// A simulation along with the systems that advance it.
+///
+/// Changes made through these methods are recorded in the journal, whereas changes made directly
+/// to `state` are not and will be missing when the journal is replayed.
+#[derive(Debug, Default, Clone)]
 pub struct SystemState {
     pub state: State,
     pub systems: SystemQueue,
+    pub journal: Journal,
 }
 
 impl SystemState {
@@ -14,14 +24,223 @@ impl SystemState {
         Self {
             state: State::new(start_date),
             systems: SystemQueue::new(start_date),
+            journal: Journal::default(),
         }
     }
 
-    pub fn update(&mut self, target: DateTime) {
-        self.systems.update(&mut self.state, target);
+    /// Registers the system, recording its name in the journal
+    pub fn register<S>(&mut self, system: S) -> Result<SystemKey, RegisterError>
+    where
+        S: CustomSystem + 'static,
+    {
+        self.register_boxed(Box::new(system))
     }
 
-    pub fn update_by(&mut self, duration: Duration) {
-        self.systems.update_by(&mut self.state, duration);
+    pub fn register_boxed(
+        &mut self,
+        system: Box<dyn CustomSystem>,
+    ) -> Result<SystemKey, RegisterError> {
+        let now = self.state.time.get_date_time();
+        let name = system.name().to_string();
+
+        let key = self.systems.register_boxed(system, now)?;
+        self.record(JournalEntry::Register(name));
+
+        Ok(key)
+    }
+
+    pub fn pause<K: Into<SystemKey>>(&mut self, key: K) {
+        let key = key.into();
+        self.record(JournalEntry::Pause(key));
+        self.systems.pause(key);
+    }
+
+    pub fn resume<K: Into<SystemKey>>(&mut self, key: K) -> Result<(), TimeError> {
+        let key = key.into();
+        let now = self.state.time.get_date_time();
+
+        self.systems.resume(key, now)?;
+        self.record(JournalEntry::Resume(key));
+
+        Ok(())
+    }
+
+    pub fn set_interval<K: Into<SystemKey>>(
+        &mut self,
+        key: K,
+        interval: Duration,
+    ) -> Result<(), TimeError> {
+        let key = key.into();
+        let now = self.state.time.get_date_time();
+
+        self.systems.set_interval(key, interval, now)?;
+        self.record(JournalEntry::SetInterval(key, interval));
+
+        Ok(())
+    }
+
+    /// Runs the command immediately, recording it in the journal
+    pub fn apply(&mut self, command: Command) -> Option<Created> {
+        self.record(JournalEntry::Apply(command.clone()));
+        command.execute(&mut self.state)
+    }
+
+    pub fn create_colony<L: Into<ColonyLinks>>(&mut self, colony: Colony, links: L) -> Id<Colony> {
+        let body = links.into().body;
+
+        match self.apply(Command::FoundColony { colony, body }) {
+            Some(Created::Colony(id)) => id,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn create_freighter<L: Into<FreighterLinks>>(
+        &mut self,
+        freighter: Freighter,
+        links: L,
+    ) -> Id<Freighter> {
+        let location = links.into().location;
+
+        match self.apply(Command::CreateFreighter {
+            freighter,
+            location,
+        }) {
+            Some(Created::Freighter(id)) => id,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Runs the command at the given date, or at the next update if the date has passed
+    pub fn schedule(&mut self, date: DateTime, command: Command) -> CommandId {
+        self.record(JournalEntry::Schedule(date, command.clone()));
+        self.systems.commands.schedule(date, command)
+    }
+
+    pub fn cancel(&mut self, id: CommandId) -> Option<Command> {
+        self.record(JournalEntry::Cancel(id));
+        self.systems.commands.cancel(id)
+    }
+
+    fn record(&mut self, entry: JournalEntry) {
+        let now = self.state.time.get_date_time();
+        self.journal.record(now, entry);
+    }
+
+    pub fn update(&mut self, target: DateTime) -> Result<(), TimeError> {
+        self.systems.update(&mut self.state, target)
+    }
+
+    pub fn update_by(&mut self, duration: Duration) -> Result<(), TimeError> {
+        self.systems.update_by(&mut self.state, duration)
+    }
+
+    /// Copies the entire simulation, including the system queue and registered systems
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.clone(),
+        }
+    }
+
+    /// Returns the simulation to the point at which the snapshot was taken
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        *self = snapshot.state.clone();
+    }
+}
+
+/// A copy of a `SystemState` that can be restored any number of times
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    state: SystemState,
+}
+
+impl Snapshot {
+    pub fn get_date_time(&self) -> DateTime {
+        self.state.state.time.get_date_time()
+    }
+}
+
+pub mod examples {
+    use super::*;
+    use crate::colony::examples::{china, european_union};
+    use crate::components::*;
+    use crate::ships::drives::Drive;
+    use crate::ships::freighter_assignment::Assignment;
+    use crate::ships::Freighter;
+    use crate::star::examples::sol_system;
+
+    /// The Sol system with a freighter shipping between colonies on Earth and Luna
+    pub fn shipping_route() -> SystemState {
+        shipping_route_from(None).0
+    }
+
+    /// The same route, but with the freighter starting at a colony on Mars, away from its route
+    pub fn shipping_route_from_mars() -> (SystemState, Id<Freighter>) {
+        shipping_route_from(Some("Mars"))
+    }
+
+    fn shipping_route_from(start: Option<&str>) -> (SystemState, Id<Freighter>) {
+        let mut state = SystemState::default();
+        state.state.create_star_system(sol_system());
+
+        let earth = state.state.body.get_by_name("Earth").unwrap();
+        let luna = state.state.body.get_by_name("Luna").unwrap();
+
+        let earth_colony = state.create_colony(european_union(), earth);
+        let luna_colony = state.create_colony(china(), luna);
+
+        let location = match start {
+            Some(name) => {
+                let body = state.state.body.get_by_name(name).unwrap();
+                state.create_colony(china(), body)
+            }
+            None => earth_colony,
+        };
+
+        let freighter = Freighter {
+            name: "Test Freighter".to_string(),
+            tonnage: 100.0 * TON,
+            capacity: 250.0 * TON,
+            loading_rate: 1.0 * TON / MIN,
+            shipping_cost: PricePerMeter::in_credits_per_kg_m(1.0 / 150e9),
+            drive: Drive::Warp(50.0 * KM / S),
+        };
+
+        let freighter = state.create_freighter(freighter, location);
+        state.apply(Command::AssignFreighter {
+            freighter,
+            assignment: Some(Assignment::Route(earth_colony, luna_colony)),
+        });
+
+        (state, freighter)
+    }
+
+    /// The saved bytes of the state, which are equal for identical states
+    pub fn save(state: &SystemState) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        state.save(&mut bytes).unwrap();
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::examples::{save, shipping_route};
+    use super::*;
+    use crate::components::*;
+
+    #[test]
+    fn restored_snapshot_replays_to_same_result() {
+        let mut state = shipping_route();
+        state.update_by(10.0 * DAY).unwrap();
+        let snapshot = state.snapshot();
+
+        state.update_by(60.0 * DAY).unwrap();
+        let expected = save(&state);
+
+        state.restore(&snapshot);
+        assert_eq!(snapshot.get_date_time(), state.state.time.get_date_time());
+
+        state.update_by(60.0 * DAY).unwrap();
+        assert_eq!(expected, save(&state));
     }
 }
@@ -1,69 +1,189 @@
+use crate::command::CommandCalendar;
 use crate::components::*;
+use crate::metrics::MetricsRecorder;
+use crate::parallel::{Access, Batch, Data, ParallelSystem};
+use crate::save::LoadError;
 use crate::state::State;
-use crate::time::{DateTime, TimeState};
-use chrono::Duration as ChronoDuration;
+use crate::stats::SystemStats;
+use crate::time::{checked_add, DateTime, TimeError, TimeState};
+use iter_context::ContextualIterator;
+use serde::{Deserialize, Serialize};
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
+use std::fmt::{Debug, Display, Formatter};
 use std::iter::FromIterator;
+use std::time::Instant;
 
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct UpdateToken {
     pub next_update: DateTime,
-    pub system: System,
+    pub system: SystemKey,
+    /// Tokens left in the queue when a system is paused or rescheduled are discarded
+    generation: u32,
 }
 
-impl UpdateToken {
-    fn run(self, state: &mut State) -> UpdateToken {
-        state.time.set_date_time(self.next_update);
+/// Identifies a system in the `SystemQueue`.
+///
+/// Systems due at the same time run in key order: built-in systems first,
+/// followed by registered systems in the order they were registered.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum SystemKey {
+    BuiltIn(System),
+    Registered(usize),
+}
 
-        self.system.run(state);
+impl From<System> for SystemKey {
+    fn from(system: System) -> Self {
+        SystemKey::BuiltIn(system)
+    }
+}
 
-        UpdateToken {
-            next_update: self.next_update + self.system.get_chrono_interval(),
-            system: self.system,
-        }
+/// A system defined outside of this crate that runs at a regular interval, e.g., a tax cycle
+///
+/// Systems are cloned along with the rest of the state when taking a snapshot.
+pub trait CustomSystem: Debug + CloneCustomSystem {
+    /// Must be unique among the registered systems
+    fn name(&self) -> &str;
+
+    /// The interval when first registered, which must be positive, see `SystemQueue::set_interval`
+    fn get_interval(&self) -> Duration;
+
+    /// Delay between registration and the first run
+    fn get_offset(&self) -> Duration {
+        Duration::zero()
+    }
+
+    fn run(&mut self, state: &mut State);
+}
+
+/// Allows boxed systems to be cloned, implemented for every `CustomSystem` that is `Clone`
+pub trait CloneCustomSystem {
+    fn clone_box(&self) -> Box<dyn CustomSystem>;
+}
+
+impl<S: CustomSystem + Clone + 'static> CloneCustomSystem for S {
+    fn clone_box(&self) -> Box<dyn CustomSystem> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn CustomSystem> {
+    fn clone(&self) -> Self {
+        self.clone_box()
     }
 }
 
 array_enum! {
     enum System {
+        type Array = struct SystemArray;
         FreighterState,
         ColonyProductionCycle,
         ColonyPopulation,
         ResourceDecay,
         PrintState,
         ShippingAverage,
+        RecordMetrics,
     }
 }
 
 impl System {
-    fn run(self, state: &mut State) {
+    fn run(self, state: &mut State, metrics: &mut MetricsRecorder, interval: Duration) {
         match self {
             System::FreighterState => {
                 state
                     .freighter
                     .update(&state.time, &mut state.colony, &state.body, &state.star)
             }
-            System::ColonyProductionCycle => state.colony.production_cycle(),
-            System::ColonyPopulation => state.colony.update_population(&mut state.body),
-            System::ResourceDecay => state.colony.resources.decay(),
+            System::ColonyProductionCycle => state.colony.production_cycle(interval),
+            System::ColonyPopulation => state.colony.update_population(&mut state.body, interval),
+            System::ResourceDecay => state.colony.resources.decay(interval),
             System::PrintState => {} // state.print(),
-            System::ShippingAverage => state.colony.resources.update_shipping_avg(),
+            System::ShippingAverage => state.colony.resources.update_shipping_avg(interval),
+            System::RecordMetrics => metrics.record(&state.colony, state.time.get_date_time()),
         }
     }
 
-    fn get_first_token(self, start: DateTime) -> UpdateToken {
-        UpdateToken {
-            next_update: start,
-            system: self,
+    /// The parts of the state used by the system, see `parallel::run`
+    pub const fn get_access(self) -> Access {
+        use Data::*;
+
+        match self {
+            System::FreighterState => Access {
+                reads: &[Time, Stars, Bodies, ColonyBody],
+                writes: &[Freighters, ColonyStockpile, ColonyShipping, ColonyMarket],
+            },
+            System::ColonyProductionCycle => Access {
+                reads: &[],
+                writes: &[
+                    ColonyPeople,
+                    ColonyStockpile,
+                    ColonyMarket,
+                    ColonyProduction,
+                ],
+            },
+            System::ColonyPopulation => Access {
+                reads: &[ColonyBody],
+                // body populations are summed through `&mut Bodies`
+                writes: &[Bodies, ColonyPeople],
+            },
+            System::ResourceDecay => Access {
+                reads: &[],
+                writes: &[ColonyStockpile],
+            },
+            System::PrintState => Access {
+                reads: &[
+                    Time,
+                    ColonyPeople,
+                    ColonyStockpile,
+                    ColonyMarket,
+                    ColonyProduction,
+                ],
+                writes: &[],
+            },
+            System::ShippingAverage => Access {
+                reads: &[],
+                writes: &[ColonyShipping],
+            },
+            System::RecordMetrics => Access {
+                reads: &[
+                    Time,
+                    ColonyPeople,
+                    ColonyStockpile,
+                    ColonyMarket,
+                    ColonyProduction,
+                ],
+                // the recorder is held by the queue rather than the state
+                writes: &[],
+            },
         }
     }
 
-    pub fn get_chrono_interval(self) -> ChronoDuration {
-        self.get_interval().into()
+    /// Systems with a split implementation in `parallel::run`
+    pub const fn to_parallel(self) -> Option<ParallelSystem> {
+        match self {
+            System::ColonyPopulation => Some(ParallelSystem::ColonyPopulation),
+            System::ResourceDecay => Some(ParallelSystem::ResourceDecay),
+            System::ShippingAverage => Some(ParallelSystem::ShippingAverage),
+            _ => None,
+        }
     }
 
-    pub const fn get_interval(self) -> Duration {
+    /// The number of entities processed in a single run, as a rough measure of its cost
+    fn count_entities(self, state: &State) -> usize {
+        match self {
+            // only idle freighters are checked, the rest move on through events
+            System::FreighterState => state.freighter.state.idle.id.iter().count(),
+            System::ColonyProductionCycle
+            | System::ColonyPopulation
+            | System::ResourceDecay
+            | System::ShippingAverage
+            | System::RecordMetrics => state.colony.alloc.ids().into_iter().count(),
+            System::PrintState => 0,
+        }
+    }
+
+    /// The interval used until changed with `SystemQueue::set_interval`
+    pub const fn get_default_interval(self) -> Duration {
         match self {
             System::FreighterState => 10.0 * MIN,
             System::ColonyProductionCycle => 1.0 * DAY,
@@ -71,17 +191,58 @@ impl System {
             System::ResourceDecay => 30.0 * DAY,
             System::PrintState => 90.0 * DAY,
             System::ShippingAverage => 7.0 * DAY,
+            System::RecordMetrics => 1.0 * DAY,
+        }
+    }
+}
+
+/// The live interval and next run time of a system
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub interval: Duration,
+    /// `None` while the system is paused
+    pub next_update: Option<DateTime>,
+    generation: u32,
+}
+
+impl Schedule {
+    fn new(interval: Duration, next_update: DateTime) -> Self {
+        Self {
+            interval,
+            next_update: Some(next_update),
+            generation: 0,
         }
     }
 
-    pub const fn get_interval_as_year_fraction(self) -> f64 {
-        self.get_interval() / Duration::in_days(365.25)
+    fn get_token(&self, system: SystemKey) -> Option<UpdateToken> {
+        self.next_update.map(|next_update| UpdateToken {
+            next_update,
+            system,
+            generation: self.generation,
+        })
     }
+
+    fn is_current(&self, token: &UpdateToken) -> bool {
+        self.generation == token.generation && self.next_update == Some(token.next_update)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RegisteredSystem {
+    system: Box<dyn CustomSystem>,
+    schedule: Schedule,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SystemQueue {
     pub queue: MinHeap<UpdateToken>,
+    pub commands: CommandCalendar,
+    schedules: SystemArray<Schedule>,
+    registered: Vec<RegisteredSystem>,
+    /// `None` unless profiling is enabled
+    stats: Option<SystemStats>,
+    /// Records nothing until set, and is not saved
+    metrics: MetricsRecorder,
 }
 
 impl Default for SystemQueue {
@@ -93,52 +254,536 @@ impl Default for SystemQueue {
 
 impl SystemQueue {
     pub fn new(start_date: DateTime) -> Self {
+        let mut schedules =
+            SystemArray::new([Schedule::new(Duration::zero(), start_date); System::LEN]);
+
+        for system in System::ARRAY.iter() {
+            schedules[*system] = Schedule::new(system.get_default_interval(), start_date);
+        }
+
         let queue = System::ARRAY
             .iter()
-            .map(|system| system.get_first_token(start_date))
+            .filter_map(|system| schedules[*system].get_token((*system).into()))
             .collect();
 
-        Self { queue }
+        Self {
+            queue,
+            commands: CommandCalendar::default(),
+            schedules,
+            registered: Vec::new(),
+            stats: None,
+            metrics: MetricsRecorder::default(),
+        }
+    }
+
+    /// Adds a system to the queue, to be run first at the given date plus the system's offset
+    pub fn register<S>(&mut self, system: S, now: DateTime) -> Result<SystemKey, RegisterError>
+    where
+        S: CustomSystem + 'static,
+    {
+        self.register_boxed(Box::new(system), now)
+    }
+
+    pub fn register_boxed(
+        &mut self,
+        system: Box<dyn CustomSystem>,
+        now: DateTime,
+    ) -> Result<SystemKey, RegisterError> {
+        if self.get_registered(system.name()).is_some() {
+            return Err(RegisterError::DuplicateName(system.name().to_string()));
+        }
+
+        // negated so that NaN is rejected too
+        if !(system.get_interval() > Duration::zero()) {
+            return Err(RegisterError::NonPositiveInterval(
+                system.name().to_string(),
+            ));
+        }
+
+        let key = SystemKey::Registered(self.registered.len());
+        let next_update = checked_add(now, system.get_offset())?;
+        let schedule = Schedule::new(system.get_interval(), next_update);
+
+        self.registered.push(RegisteredSystem { system, schedule });
+        self.push_next(key);
+
+        Ok(key)
+    }
+
+    pub fn get_registered(&self, name: &str) -> Option<SystemKey> {
+        self.registered
+            .iter()
+            .position(|registered| registered.system.name() == name)
+            .map(SystemKey::Registered)
+    }
+
+    pub fn get_schedule<K: Into<SystemKey>>(&self, key: K) -> &Schedule {
+        match key.into() {
+            SystemKey::BuiltIn(system) => &self.schedules[system],
+            SystemKey::Registered(index) => &self.registered[index].schedule,
+        }
+    }
+
+    fn get_schedule_mut(&mut self, key: SystemKey) -> &mut Schedule {
+        match key {
+            SystemKey::BuiltIn(system) => &mut self.schedules[system],
+            SystemKey::Registered(index) => &mut self.registered[index].schedule,
+        }
+    }
+
+    /// Starts recording the run count and wall time of each built-in system, discarding any previous stats
+    pub fn enable_profiling(&mut self) {
+        self.stats = Some(SystemStats::default());
+    }
+
+    pub fn disable_profiling(&mut self) -> Option<SystemStats> {
+        self.stats.take()
+    }
+
+    pub fn get_stats(&self) -> Option<&SystemStats> {
+        self.stats.as_ref()
+    }
+
+    /// Replaces the recorder sampled by the `RecordMetrics` system, discarding any previous samples
+    pub fn set_metrics(&mut self, metrics: MetricsRecorder) {
+        self.metrics = metrics;
+    }
+
+    pub fn get_metrics(&self) -> &MetricsRecorder {
+        &self.metrics
+    }
+
+    /// Lists every system with its next run time, which is `None` for paused systems
+    pub fn get_next_updates(&self) -> Vec<(SystemKey, Option<DateTime>)> {
+        let built_in = System::ARRAY.iter().map(|system| SystemKey::from(*system));
+        let registered = (0..self.registered.len()).map(SystemKey::Registered);
+
+        built_in
+            .chain(registered)
+            .map(|key| (key, self.get_schedule(key).next_update))
+            .collect()
+    }
+
+    pub fn get_interval<K: Into<SystemKey>>(&self, key: K) -> Duration {
+        self.get_schedule(key).interval
+    }
+
+    pub fn is_paused<K: Into<SystemKey>>(&self, key: K) -> bool {
+        self.get_schedule(key).next_update.is_none()
+    }
+
+    pub fn pause<K: Into<SystemKey>>(&mut self, key: K) {
+        let schedule = self.get_schedule_mut(key.into());
+        schedule.next_update = None;
+        schedule.generation += 1;
+    }
+
+    /// Schedules a paused system to run one interval after the given date
+    pub fn resume<K: Into<SystemKey>>(&mut self, key: K, now: DateTime) -> Result<(), TimeError> {
+        let key = key.into();
+
+        if self.is_paused(key) {
+            let interval = self.get_interval(key);
+            self.reschedule(key, checked_add(now, interval)?);
+        }
+
+        Ok(())
     }
 
-    pub fn update(&mut self, state: &mut State, target: DateTime) {
-        while !self.target_reached(target) {
-            let current_update = self.pop();
-            let next_update = current_update.run(state);
-            self.push(next_update);
+    /// Changes the interval of a system.
+    ///
+    /// The pending run is moved to one new interval after the previous run, but no earlier than the given date.
+    /// Nothing is changed if the new run date is out of range.
+    pub fn set_interval<K: Into<SystemKey>>(
+        &mut self,
+        key: K,
+        interval: Duration,
+        now: DateTime,
+    ) -> Result<(), TimeError> {
+        check_interval(interval)?;
+
+        let key = key.into();
+        let schedule = self.get_schedule(key);
+
+        let next_update = match schedule.next_update {
+            Some(next_update) => {
+                let last_update = checked_add(next_update, -schedule.interval)?;
+                Some(checked_add(last_update, interval)?.max(now))
+            }
+            None => None,
+        };
+
+        self.get_schedule_mut(key).interval = interval;
+
+        if let Some(next_update) = next_update {
+            self.reschedule(key, next_update);
         }
 
-        state.time.set_date_time(target);
+        Ok(())
+    }
+
+    fn reschedule(&mut self, key: SystemKey, next_update: DateTime) {
+        let schedule = self.get_schedule_mut(key);
+        schedule.next_update = Some(next_update);
+        schedule.generation += 1;
+
+        self.push_next(key);
     }
 
-    pub fn update_by(&mut self, state: &mut State, duration: Duration) {
-        let time = state.time.get_date_time() + ChronoDuration::from(duration);
-        self.update(state, time);
+    fn push_next(&mut self, key: SystemKey) {
+        if let Some(token) = self.get_schedule(key).get_token(key) {
+            self.push(token);
+        }
+    }
+
+    /// Runs every command, event and system due up to and including the target date
+    pub fn update(&mut self, state: &mut State, target: DateTime) -> Result<(), TimeError> {
+        let now = state.time.get_date_time();
+        if target < now {
+            return Err(TimeError::TargetInPast { now, target });
+        }
+
+        let precedes =
+            |date: DateTime, other: Option<DateTime>| other.map_or(true, |other| date <= other);
+
+        let target_time = state.time.get_time_at(target);
+
+        loop {
+            let next_command = self.commands.get_next_date().filter(|date| *date <= target);
+            // only due events are converted, as later ones may be beyond the supported dates
+            let next_event = state
+                .get_next_event()
+                .filter(|time| *time <= target_time)
+                .map(|time| {
+                    let date_time = state.time.get_date_time_at(time)?;
+                    Ok((time, date_time))
+                })
+                .transpose()?;
+            let next_update = self.peek().map(|token| token.next_update);
+
+            if next_update.is_none() && self.is_scheduled() {
+                return Err(TimeError::EmptyQueue);
+            }
+
+            let next_update = next_update.filter(|next_update| *next_update <= target);
+
+            // commands run before events, and events before systems scheduled for the same instant
+            match (next_command, next_event, next_update) {
+                (Some(date_time), next_event, next_update)
+                    if precedes(date_time, next_event.map(|(_, date_time)| date_time))
+                        && precedes(date_time, next_update) =>
+                {
+                    if let Some((date_time, command)) = self.commands.pop() {
+                        let date_time = date_time.max(state.time.get_date_time());
+                        state.time.set_date_time(date_time)?;
+
+                        match &mut self.stats {
+                            Some(stats) => {
+                                let start = Instant::now();
+                                command.execute(state);
+                                stats.record_command(start.elapsed());
+                            }
+                            None => {
+                                command.execute(state);
+                            }
+                        }
+                    }
+                }
+                (_, Some((time, date_time)), next_update) if precedes(date_time, next_update) => {
+                    let date_time = date_time.max(state.time.get_date_time());
+                    state.time.set_date_time(date_time)?;
+
+                    match &mut self.stats {
+                        Some(stats) => {
+                            let start = Instant::now();
+                            let entities = state.run_events(time);
+                            stats.record_events(start.elapsed(), entities);
+                        }
+                        None => {
+                            state.run_events(time);
+                        }
+                    }
+                }
+                (_, _, Some(_)) => {
+                    let (mut tokens, batch) = self.pop_batch();
+
+                    if batch.len() > 1 {
+                        self.run_batch(tokens, &batch, state)?;
+                    } else if let Some(token) = tokens.pop() {
+                        self.run(token, state)?;
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        state.time.set_date_time(target)
+    }
+
+    pub fn update_by(&mut self, state: &mut State, duration: Duration) -> Result<(), TimeError> {
+        let time = checked_add(state.time.get_date_time(), duration)?;
+        self.update(state, time)
+    }
+
+    /// True if any system is waiting to run
+    fn is_scheduled(&self) -> bool {
+        self.get_next_updates()
+            .iter()
+            .any(|(_, next_update)| next_update.is_some())
+    }
+
+    fn run(&mut self, token: UpdateToken, state: &mut State) -> Result<(), TimeError> {
+        state.time.set_date_time(token.next_update)?;
+
+        let interval = self.get_interval(token.system);
+        check_interval(interval)?;
+
+        match token.system {
+            SystemKey::BuiltIn(system) => match &mut self.stats {
+                Some(stats) => {
+                    let entities = system.count_entities(state);
+                    let start = Instant::now();
+                    system.run(state, &mut self.metrics, interval);
+                    stats.record(system, start.elapsed(), entities);
+                }
+                None => system.run(state, &mut self.metrics, interval),
+            },
+            SystemKey::Registered(index) => self.registered[index].system.run(state),
+        }
+
+        let next_update = checked_add(token.next_update, interval)?;
+        self.reschedule(token.system, next_update);
+
+        Ok(())
     }
 
-    fn target_reached(&self, target: DateTime) -> bool {
-        let next_update = self.peek().next_update;
-        next_update > target
+    /// Pops the next token, along with any following tokens for the same instant whose systems can run alongside it.
+    ///
+    /// Only consecutive tokens are batched, so that systems still run in the same order as they would one at a time.
+    /// The batch is empty if the first system cannot run in parallel.
+    fn pop_batch(&mut self) -> (Vec<UpdateToken>, Batch) {
+        let mut tokens: Vec<UpdateToken> = self.queue.pop().into_iter().collect();
+        let mut batch = Batch::default();
+
+        let first = match tokens.first() {
+            Some(token) => token.clone(),
+            None => return (tokens, batch),
+        };
+
+        match get_parallel(&first) {
+            Some(system) => batch.add(system, self.get_interval(first.system)),
+            None => return (tokens, batch),
+        }
+
+        while let Some(next) = self.peek() {
+            let key = next.system;
+            let system = match get_parallel(next) {
+                Some(system) if next.next_update == first.next_update && batch.can_add(system) => {
+                    system
+                }
+                _ => break,
+            };
+
+            if let Some(token) = self.queue.pop() {
+                tokens.push(token);
+                batch.add(system, self.get_interval(key));
+            }
+        }
+
+        (tokens, batch)
     }
 
-    fn peek(&self) -> &UpdateToken {
-        self.queue.peek().expect("system queue is empty")
+    fn run_batch(
+        &mut self,
+        tokens: Vec<UpdateToken>,
+        batch: &Batch,
+        state: &mut State,
+    ) -> Result<(), TimeError> {
+        if let Some(token) = tokens.first() {
+            state.time.set_date_time(token.next_update)?;
+        }
+
+        let entities = batch
+            .iter()
+            .map(|(system, _)| match self.stats {
+                Some(_) => system.get_system().count_entities(state),
+                None => 0,
+            })
+            .collect::<Vec<_>>();
+
+        let times = crate::parallel::run(batch, state);
+
+        if let Some(stats) = &mut self.stats {
+            for (((system, _), time), entities) in batch.iter().zip(times).zip(entities) {
+                stats.record(system.get_system(), time, entities);
+            }
+        }
+
+        for token in tokens {
+            let interval = self.get_interval(token.system);
+            let next_update = checked_add(token.next_update, interval)?;
+            self.reschedule(token.system, next_update);
+        }
+
+        Ok(())
     }
 
-    fn pop(&mut self) -> UpdateToken {
-        self.queue.pop().expect("system queue is empty")
+    /// Returns the next token in the queue, discarding those of paused or rescheduled systems
+    fn peek(&mut self) -> Option<&UpdateToken> {
+        while let Some(token) = self.queue.peek() {
+            if self.get_schedule(token.system).is_current(token) {
+                break;
+            }
+
+            self.queue.pop();
+        }
+
+        self.queue.peek()
     }
 
     fn push(&mut self, token: UpdateToken) {
         self.queue.push(token);
     }
+
+    /// The tokens in the queue that will run, in the order that they will run
+    pub fn get_pending_updates(&self) -> Vec<UpdateToken> {
+        let mut tokens = self
+            .queue
+            .iter()
+            .filter(|token| self.get_schedule(token.system).is_current(token))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        tokens.sort();
+        tokens
+    }
+
+    pub fn to_save(&self) -> SystemQueueSave {
+        let registered = self
+            .registered
+            .iter()
+            .map(|registered| (registered.system.name().to_string(), registered.schedule))
+            .collect();
+
+        SystemQueueSave {
+            queue: self.get_pending_updates(),
+            commands: self.commands.clone(),
+            schedules: self.schedules,
+            registered,
+        }
+    }
+
+    /// Restores a saved queue, matching the given systems to the saved schedules by name
+    pub fn from_save(
+        save: SystemQueueSave,
+        mut systems: Vec<Box<dyn CustomSystem>>,
+    ) -> Result<Self, LoadError> {
+        let mut registered = Vec::with_capacity(save.registered.len());
+
+        for (name, schedule) in save.registered {
+            let index = systems
+                .iter()
+                .position(|system| system.name() == name)
+                .ok_or(LoadError::MissingSystem(name))?;
+
+            registered.push(RegisteredSystem {
+                system: systems.swap_remove(index),
+                schedule,
+            });
+        }
+
+        if let Some(system) = systems.first() {
+            return Err(LoadError::UnexpectedSystem(system.name().to_string()));
+        }
+
+        Ok(Self {
+            queue: save.queue.into_iter().collect(),
+            commands: save.commands,
+            schedules: save.schedules,
+            registered,
+            stats: None,
+            metrics: MetricsRecorder::default(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RegisterError {
+    /// Another system is already registered with the name
+    DuplicateName(String),
+    /// The system would run again without time passing
+    NonPositiveInterval(String),
+    /// The offset of the first run is out of range
+    Time(TimeError),
+}
+
+impl Display for RegisterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegisterError::DuplicateName(name) => {
+                write!(f, "a system is already registered as {}", name)
+            }
+            RegisterError::NonPositiveInterval(name) => {
+                write!(f, "the interval of {} is not positive", name)
+            }
+            RegisterError::Time(error) => write!(f, "invalid first run: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for RegisterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RegisterError::Time(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<TimeError> for RegisterError {
+    fn from(error: TimeError) -> Self {
+        RegisterError::Time(error)
+    }
+}
+
+/// The pending tokens and schedules of a `SystemQueue`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SystemQueueSave {
+    queue: Vec<UpdateToken>,
+    commands: CommandCalendar,
+    schedules: SystemArray<Schedule>,
+    registered: Vec<(String, Schedule)>,
 }
 
-#[derive(Debug, Default)]
+/// Rejects intervals that would run a system again without time passing, including NaN
+fn check_interval(interval: Duration) -> Result<(), TimeError> {
+    if interval > Duration::zero() {
+        Ok(())
+    } else {
+        Err(TimeError::NonPositiveInterval)
+    }
+}
+
+fn get_parallel(token: &UpdateToken) -> Option<ParallelSystem> {
+    match token.system {
+        SystemKey::BuiltIn(system) => system.to_parallel(),
+        SystemKey::Registered(_) => None,
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct MinHeap<T: Ord> {
     heap: BinaryHeap<Reverse<T>>,
 }
 
+impl<T: Ord> Default for MinHeap<T> {
+    fn default() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+        }
+    }
+}
+
 impl<T: Ord> MinHeap<T> {
     pub fn push(&mut self, value: T) {
         self.heap.push(Reverse(value));
@@ -151,6 +796,11 @@ impl<T: Ord> MinHeap<T> {
     pub fn peek(&self) -> Option<&T> {
         self.heap.peek().map(|rev| &rev.0)
     }
+
+    /// Every value in an arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.heap.iter().map(|rev| &rev.0)
+    }
 }
 
 impl<T: Ord> FromIterator<T> for MinHeap<T> {
@@ -159,3 +809,476 @@ impl<T: Ord> FromIterator<T> for MinHeap<T> {
         Self { heap }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::examples::china;
+    use crate::command::Command;
+    use crate::ships::drives::Drive;
+    use crate::ships::freighter_state::FreighterStateIndex;
+    use crate::system_state::examples::{shipping_route, shipping_route_from_mars};
+    use crate::system_state::SystemState;
+    use crate::time::{get_date, ChronoDuration};
+    use gen_id::Valid;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Debug, Clone)]
+    struct LoggingSystem {
+        name: &'static str,
+        interval: Duration,
+        offset: Duration,
+        log: Rc<RefCell<Vec<(&'static str, DateTime)>>>,
+    }
+
+    impl CustomSystem for LoggingSystem {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn get_interval(&self) -> Duration {
+            self.interval
+        }
+
+        fn get_offset(&self) -> Duration {
+            self.offset
+        }
+
+        fn run(&mut self, state: &mut State) {
+            let now = state.time.get_date_time();
+            self.log.borrow_mut().push((self.name, now));
+        }
+    }
+
+    #[test]
+    fn registered_systems_run_at_interval_in_registration_order() {
+        let start = get_date(2050, 1, 1);
+        let mut state = SystemState::new(start);
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        state
+            .register(LoggingSystem {
+                name: "B",
+                interval: 2.0 * DAY,
+                offset: 1.0 * DAY,
+                log: log.clone(),
+            })
+            .unwrap();
+        state
+            .register(LoggingSystem {
+                name: "A",
+                interval: 1.0 * DAY,
+                offset: Duration::zero(),
+                log: log.clone(),
+            })
+            .unwrap();
+
+        state.update(get_date(2050, 1, 4)).unwrap();
+
+        let expected = vec![
+            ("A", get_date(2050, 1, 1)),
+            ("B", get_date(2050, 1, 2)),
+            ("A", get_date(2050, 1, 2)),
+            ("A", get_date(2050, 1, 3)),
+            ("B", get_date(2050, 1, 4)),
+            ("A", get_date(2050, 1, 4)),
+        ];
+
+        assert_eq!(expected, *log.borrow());
+    }
+
+    #[test]
+    fn registered_system_names_are_unique() {
+        let mut state = SystemState::default();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        let first = state.register(logging_system(&log));
+        let second = state.register(logging_system(&log));
+
+        assert_eq!(Ok(SystemKey::Registered(0)), first);
+        assert_eq!(Err(RegisterError::DuplicateName("A".to_string())), second);
+        assert_eq!(System::LEN + 1, state.systems.get_next_updates().len());
+    }
+
+    #[test]
+    fn registered_system_intervals_are_positive() {
+        let mut state = SystemState::default();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        let zero = LoggingSystem {
+            interval: Duration::zero(),
+            ..logging_system(&log)
+        };
+        let negative = LoggingSystem {
+            interval: -1.0 * DAY,
+            ..logging_system(&log)
+        };
+
+        assert_eq!(
+            Err(RegisterError::NonPositiveInterval("A".to_string())),
+            state.register(zero)
+        );
+        assert_eq!(
+            Err(RegisterError::NonPositiveInterval("A".to_string())),
+            state.register(negative)
+        );
+        assert_eq!(System::LEN, state.systems.get_next_updates().len());
+    }
+
+    #[test]
+    fn freighter_arrival_is_not_rounded_to_system_interval() {
+        let (mut state, freighter) = shipping_route_from_mars();
+
+        // the first update assigns the idle freighter, sending it to the nearest colony
+        state.update_by(Duration::in_s(1.0)).unwrap();
+
+        let freighter_state = &state.state.freighter.state;
+        let arrival = match freighter_state.indices().get(Valid::assert(freighter)) {
+            FreighterStateIndex::Moving(index) => {
+                *freighter_state.moving.arrival.get(index).unwrap()
+            }
+            _ => panic!("freighter should be moving"),
+        };
+
+        let before_arrival = state.state.time.get_date_time_at(arrival - S).unwrap();
+        state.update(before_arrival).unwrap();
+
+        let is_moving = |state: &SystemState| {
+            matches!(
+                state
+                    .state
+                    .freighter
+                    .state
+                    .indices()
+                    .get(Valid::assert(freighter)),
+                FreighterStateIndex::Moving(_)
+            )
+        };
+
+        assert!(is_moving(&state));
+
+        let after_arrival = state.state.time.get_date_time_at(arrival + S).unwrap();
+        state.update(after_arrival).unwrap();
+
+        assert!(!is_moving(&state));
+    }
+
+    #[test]
+    fn freighter_departs_when_loading_completes() {
+        let (mut state, freighter) = shipping_route_from_mars();
+        let id = Valid::assert(freighter);
+
+        // the freighter arrives empty, so it starts loading as soon as it arrives
+        state.update_by(Duration::in_s(1.0)).unwrap();
+
+        let freighter_state = &state.state.freighter.state;
+        let arrival = match freighter_state.indices().get(id) {
+            FreighterStateIndex::Moving(index) => {
+                *freighter_state.moving.arrival.get(index).unwrap()
+            }
+            _ => panic!("freighter should be moving"),
+        };
+
+        let after_arrival = state.state.time.get_date_time_at(arrival + S).unwrap();
+        state.update(after_arrival).unwrap();
+
+        let freighter_state = &state.state.freighter.state;
+        let completion = match freighter_state.indices().get(id) {
+            FreighterStateIndex::Loading(index) => {
+                *freighter_state.loading.completion.get(index).unwrap()
+            }
+            _ => panic!("freighter should be loading"),
+        };
+
+        assert!(completion > arrival + S);
+
+        let before_completion = state.state.time.get_date_time_at(completion - S).unwrap();
+        state.update(before_completion).unwrap();
+
+        let freighter_state = &state.state.freighter.state;
+        assert!(matches!(
+            freighter_state.indices().get(id),
+            FreighterStateIndex::Loading(_)
+        ));
+
+        let after_completion = state.state.time.get_date_time_at(completion + S).unwrap();
+        state.update(after_completion).unwrap();
+
+        let freighter_state = &state.state.freighter.state;
+        match freighter_state.indices().get(id) {
+            FreighterStateIndex::Moving(index) => {
+                assert_eq!(
+                    completion,
+                    *freighter_state.moving.departure.get(index).unwrap()
+                );
+            }
+            _ => panic!("freighter should be moving"),
+        }
+    }
+
+    #[test]
+    fn out_of_range_arrival_does_not_stop_updates() {
+        let (mut state, freighter) = shipping_route_from_mars();
+        let id = Valid::assert(freighter);
+
+        // slow enough that the arrival is beyond the supported dates
+        state
+            .state
+            .freighter
+            .drive
+            .insert(id, Drive::Warp(1e-20 * M / S));
+
+        state.update_by(Duration::in_s(1.0)).unwrap();
+
+        let freighter_state = &state.state.freighter.state;
+        let arrival = match freighter_state.indices().get(id) {
+            FreighterStateIndex::Moving(index) => {
+                *freighter_state.moving.arrival.get(index).unwrap()
+            }
+            _ => panic!("freighter should be moving"),
+        };
+
+        assert_eq!(
+            Err(TimeError::Overflow),
+            state.state.time.get_date_time_at(arrival)
+        );
+
+        state.update_by(30.0 * DAY).unwrap();
+        state.update_by(30.0 * DAY).unwrap();
+    }
+
+    fn logging_system(log: &Rc<RefCell<Vec<(&'static str, DateTime)>>>) -> LoggingSystem {
+        LoggingSystem {
+            name: "A",
+            interval: 1.0 * DAY,
+            offset: Duration::zero(),
+            log: log.clone(),
+        }
+    }
+
+    #[test]
+    fn paused_systems_do_not_run_until_resumed() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let key = state.register(logging_system(&log)).unwrap();
+
+        state.update(get_date(2050, 1, 2)).unwrap();
+        state.pause(key);
+        state.update(get_date(2050, 1, 5)).unwrap();
+
+        assert!(state.systems.is_paused(key));
+        assert_eq!(None, state.systems.get_schedule(key).next_update);
+
+        state.resume(key).unwrap();
+        state.update(get_date(2050, 1, 7)).unwrap();
+
+        let dates = log
+            .borrow()
+            .iter()
+            .map(|(_, date)| *date)
+            .collect::<Vec<_>>();
+        let expected = vec![
+            get_date(2050, 1, 1),
+            get_date(2050, 1, 2),
+            get_date(2050, 1, 6),
+            get_date(2050, 1, 7),
+        ];
+
+        assert_eq!(expected, dates);
+    }
+
+    #[test]
+    fn set_interval_reschedules_pending_run() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let key = state.register(logging_system(&log)).unwrap();
+
+        state.update(get_date(2050, 1, 1)).unwrap();
+        state.set_interval(key, 3.0 * DAY).unwrap();
+
+        assert_eq!(3.0 * DAY, state.systems.get_interval(key));
+        assert_eq!(
+            Some(get_date(2050, 1, 4)),
+            state.systems.get_schedule(key).next_update
+        );
+
+        state.update(get_date(2050, 1, 8)).unwrap();
+
+        let dates = log
+            .borrow()
+            .iter()
+            .map(|(_, date)| *date)
+            .collect::<Vec<_>>();
+        let expected = vec![
+            get_date(2050, 1, 1),
+            get_date(2050, 1, 4),
+            get_date(2050, 1, 7),
+        ];
+
+        assert_eq!(expected, dates);
+    }
+
+    #[test]
+    fn set_interval_rejects_non_positive_intervals() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let key = state.register(logging_system(&log)).unwrap();
+
+        assert_eq!(
+            Err(TimeError::NonPositiveInterval),
+            state.set_interval(key, Duration::zero())
+        );
+        assert_eq!(
+            Err(TimeError::NonPositiveInterval),
+            state.set_interval(key, -1.0 * DAY)
+        );
+        assert_eq!(1.0 * DAY, state.systems.get_interval(key));
+
+        state.update(get_date(2050, 1, 3)).unwrap();
+        assert_eq!(3, log.borrow().len());
+    }
+
+    #[test]
+    fn built_in_systems_can_be_paused() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+        state.pause(System::ColonyPopulation);
+
+        let next_updates = state.systems.get_next_updates();
+
+        assert!(next_updates.contains(&(System::ColonyPopulation.into(), None)));
+        assert!(next_updates.contains(&(
+            System::ColonyProductionCycle.into(),
+            Some(get_date(2050, 1, 1))
+        )));
+    }
+
+    #[test]
+    fn profiling_records_built_in_system_runs() {
+        let mut state = shipping_route();
+        assert!(state.systems.get_stats().is_none());
+
+        let mars = state.state.body.get_by_name("Mars").unwrap();
+        let colony = Command::FoundColony {
+            colony: china(),
+            body: mars,
+        };
+        // after the last production run, so that it does not change the entity counts
+        state.schedule(get_date(2050, 1, 11) + ChronoDuration::seconds(30), colony);
+
+        state.systems.enable_profiling();
+        state.update_by(10.0 * DAY + MIN).unwrap();
+
+        let stats = state.systems.get_stats().unwrap();
+        let production = stats.get(System::ColonyProductionCycle);
+
+        // runs at the start and after each of the ten days
+        assert_eq!(11, production.runs);
+        assert_eq!(22, production.entities);
+        assert!(production.max_time <= production.total_time);
+        assert_eq!(1441, stats.get(System::FreighterState).runs);
+        assert!(stats.get_events().runs > 0);
+        assert_eq!(1, stats.get_commands().runs);
+        assert_eq!(System::LEN + 3, stats.to_csv().lines().count());
+    }
+
+    #[test]
+    fn parallel_systems_match_sequential_runs() {
+        let parallel_systems = [
+            System::ColonyPopulation,
+            System::ResourceDecay,
+            System::ShippingAverage,
+        ];
+
+        let mut parallel = shipping_route();
+        let mut sequential = shipping_route();
+
+        for system in System::ARRAY.iter() {
+            if parallel_systems.contains(system) {
+                parallel.set_interval(*system, 5.0 * DAY).unwrap();
+            } else {
+                parallel.pause(*system);
+            }
+
+            sequential.pause(*system);
+        }
+
+        parallel.update_by(30.0 * DAY).unwrap();
+
+        for _ in 0..7 {
+            let state = &mut sequential.state;
+            state.colony.update_population(&mut state.body, 5.0 * DAY);
+            state.colony.resources.decay(5.0 * DAY);
+            state.colony.resources.update_shipping_avg(5.0 * DAY);
+        }
+
+        let serialize = |state: &SystemState| {
+            (
+                bincode::serialize(&state.state.colony).unwrap(),
+                bincode::serialize(&state.state.body).unwrap(),
+            )
+        };
+
+        assert_eq!(serialize(&sequential), serialize(&parallel));
+    }
+
+    #[test]
+    fn update_to_past_date_is_an_error() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+        state.update(get_date(2050, 1, 5)).unwrap();
+
+        let result = state.update(get_date(2050, 1, 3));
+
+        assert_eq!(
+            Err(TimeError::TargetInPast {
+                now: get_date(2050, 1, 5),
+                target: get_date(2050, 1, 3)
+            }),
+            result
+        );
+        assert_eq!(get_date(2050, 1, 5), state.state.time.get_date_time());
+    }
+
+    #[test]
+    fn update_by_out_of_range_duration_is_an_error() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+
+        assert_eq!(Err(TimeError::Overflow), state.update_by(Duration::MAX));
+        assert_eq!(Err(TimeError::Overflow), state.update_by(1e9 * YR));
+    }
+
+    #[test]
+    fn out_of_range_schedules_are_an_error() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        let distant = LoggingSystem {
+            offset: Duration::MAX,
+            ..logging_system(&log)
+        };
+        assert_eq!(
+            Err(RegisterError::Time(TimeError::Overflow)),
+            state.register(distant)
+        );
+
+        let key = state.register(logging_system(&log)).unwrap();
+        let before = state.systems.get_next_updates();
+
+        assert_eq!(Err(TimeError::Overflow), state.set_interval(key, 1e9 * YR));
+        assert_eq!(before, state.systems.get_next_updates());
+        assert_eq!(1.0 * DAY, state.systems.get_interval(key));
+
+        state.pause(key);
+        state.set_interval(key, 1e9 * YR).unwrap();
+        assert_eq!(Err(TimeError::Overflow), state.resume(key));
+        assert!(state.systems.is_paused(key));
+    }
+
+    #[test]
+    fn missing_tokens_are_an_error() {
+        let mut state = SystemState::new(get_date(2050, 1, 1));
+        while state.systems.queue.pop().is_some() {}
+
+        assert_eq!(Err(TimeError::EmptyQueue), state.update_by(1.0 * DAY));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::components::{Duration, TimeFloat};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result};
 use std::ops::AddAssign;
 
@@ -7,7 +8,7 @@ pub type DateTime = NaiveDateTime;
 pub type StdDuration = std::time::Duration;
 pub type ChronoDuration = chrono::Duration;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeState {
     game_time: DateTime,
     time_float: TimeFloat,
@@ -23,10 +24,18 @@ impl TimeState {
         }
     }
 
-    pub fn set_date_time(&mut self, date_time: DateTime) {
-        debug_assert!(date_time >= self.game_time);
+    pub fn set_date_time(&mut self, date_time: DateTime) -> std::result::Result<(), TimeError> {
+        if date_time < self.game_time {
+            return Err(TimeError::TargetInPast {
+                now: self.game_time,
+                target: date_time,
+            });
+        }
+
         self.game_time = date_time;
         self.time_float = self.calculate_time_float();
+
+        Ok(())
     }
 
     pub fn get_date_time(&self) -> DateTime {
@@ -37,12 +46,21 @@ impl TimeState {
         self.time_float
     }
 
-    fn calculate_time_float(&self) -> TimeFloat {
-        let duration: ChronoDuration = self.game_time - self.start_date;
+    pub fn get_date_time_at(&self, time: TimeFloat) -> std::result::Result<DateTime, TimeError> {
+        checked_add(self.start_date, time.value)
+    }
+
+    /// The inverse of `get_date_time_at`, for dates that need not match the current time
+    pub fn get_time_at(&self, date_time: DateTime) -> TimeFloat {
+        let duration: ChronoDuration = date_time - self.start_date;
         let seconds = duration.num_milliseconds() as f64 / 1e3;
         TimeFloat::in_s(seconds)
     }
 
+    fn calculate_time_float(&self) -> TimeFloat {
+        self.get_time_at(self.game_time)
+    }
+
     pub fn print(&self) {
         println!("{}\n", self);
     }
@@ -58,7 +76,8 @@ impl AddAssign<StdDuration> for TimeState {
     fn add_assign(&mut self, rhs: StdDuration) {
         let duration = ChronoDuration::from_std(rhs).unwrap();
         let new_date_time = self.game_time + duration;
-        self.set_date_time(new_date_time);
+        self.set_date_time(new_date_time)
+            .expect("a positive duration cannot move time backwards");
     }
 }
 
@@ -74,6 +93,56 @@ impl Default for TimeState {
     }
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TimeError {
+    TargetInPast {
+        now: DateTime,
+        target: DateTime,
+    },
+    /// A system is scheduled to run but its token is missing from the queue
+    EmptyQueue,
+    /// The date is outside of the range supported by `chrono`
+    Overflow,
+    /// A system would run again without time passing
+    NonPositiveInterval,
+}
+
+impl Display for TimeError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            TimeError::TargetInPast { now, target } => {
+                write!(
+                    f,
+                    "target date {} is before the current date {}",
+                    target, now
+                )
+            }
+            TimeError::EmptyQueue => write!(f, "scheduled systems are missing from the queue"),
+            TimeError::Overflow => write!(f, "date is out of range"),
+            TimeError::NonPositiveInterval => write!(f, "system interval is not positive"),
+        }
+    }
+}
+
+impl std::error::Error for TimeError {}
+
+/// Adds a game duration to a date, failing instead of panicking if the result is out of range
+pub fn checked_add(
+    date_time: DateTime,
+    duration: Duration,
+) -> std::result::Result<DateTime, TimeError> {
+    // beyond this, the conversion to microseconds saturates
+    const MAX_SECONDS: f64 = i64::MAX as f64 / 1e6;
+
+    if !duration.value.is_finite() || duration.value.abs() >= MAX_SECONDS {
+        return Err(TimeError::Overflow);
+    }
+
+    date_time
+        .checked_add_signed(ChronoDuration::from(duration))
+        .ok_or(TimeError::Overflow)
+}
+
 fn starting_date() -> DateTime {
     get_date(2050, 1, 1)
 }
@@ -84,6 +153,20 @@ pub fn get_date(year: i32, month: u32, day: u32) -> DateTime {
     NaiveDateTime::new(date, time)
 }
 
+#[test]
+fn distant_times_are_out_of_range() {
+    let time = TimeState::new(get_date(2050, 1, 1));
+
+    assert_eq!(
+        Ok(get_date(2050, 1, 2)),
+        time.get_date_time_at(TimeFloat::in_s(Duration::SECONDS_PER_DAY))
+    );
+    assert_eq!(
+        Err(TimeError::Overflow),
+        time.get_date_time_at(TimeFloat::in_s(1e18))
+    );
+}
+
 #[test]
 fn time_size_tests() {
     assert_eq!(16, std::mem::size_of::<ChronoDuration>());